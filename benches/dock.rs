@@ -0,0 +1,90 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+// The same docks as the tests use
+#[path = "../tests/common/mod.rs"]
+mod common;
+use common::{dock_with_panes, header_title_point};
+
+const PANE_COUNTS: [i32; 3] = [10, 100, 1000];
+// The number of pointer moves in a simulated drag, and how far each one goes
+const DRAG_STEPS: usize = 100;
+const DRAG_STEP_DISTANCE: f64 = -10.0;
+
+// What the dock computes for the panes in each layout pass
+fn layout(c: &mut Criterion) {
+    let mut group = c.benchmark_group("layout");
+    for count in PANE_COUNTS {
+        let (mut model, data) = dock_with_panes(count);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| {
+                model.snap_if_not_animating(&data);
+                for index in 0..model.panes().len() {
+                    black_box(model.contents_rect(index));
+                    black_box(model.header_rect(index));
+                }
+                black_box(model.input_area());
+            })
+        });
+    }
+    group.finish();
+}
+
+// Matching the panes to the data, which update_pane_widgets does before it adds or parks widgets
+fn diffing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("diffing");
+    for count in PANE_COUNTS {
+        let (mut model, data) = dock_with_panes(count);
+        group.bench_with_input(BenchmarkId::new("unchanged", count), &count, |b, _| {
+            b.iter(|| black_box(model.sync(&data)))
+        });
+
+        // Each iteration closes the first pane and brings it back
+        let mut closed_data = data.clone();
+        closed_data.close_pane(0);
+        group.bench_with_input(BenchmarkId::new("close_and_reopen", count), &count, |b, _| {
+            b.iter(|| {
+                black_box(model.sync(&closed_data));
+                black_box(model.sync(&data));
+            })
+        });
+
+        // Each iteration moves the first pane to the end and back
+        let mut reordered_data = data.clone();
+        let first_pane = reordered_data.panes.pop_front().unwrap();
+        reordered_data.panes.push_back(first_pane);
+        group.bench_with_input(BenchmarkId::new("reorder", count), &count, |b, _| {
+            b.iter(|| {
+                black_box(model.sync(&reordered_data));
+                black_box(model.sync(&data));
+            })
+        });
+    }
+    group.finish();
+}
+
+// Pressing on the header of the pane at the bound side, and dragging it over the panes next to it
+fn drag(c: &mut Criterion) {
+    let mut group = c.benchmark_group("drag");
+    for count in PANE_COUNTS {
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, count| {
+            b.iter_batched(
+                || dock_with_panes(*count),
+                |(mut model, mut data)| {
+                    let mut pos = header_title_point(&model, 0);
+                    model.pointer_down(pos, 1, &mut data);
+                    for _ in 0..DRAG_STEPS {
+                        pos.x += DRAG_STEP_DISTANCE;
+                        black_box(model.pointer_move(pos, &mut data));
+                    }
+                    black_box(model.pointer_up(pos, &mut data));
+                    (model, data)
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, layout, diffing, drag);
+criterion_main!(benches);
@@ -0,0 +1,180 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use clap::Parser;
+use druid::{Point, Rect, Size};
+use serde::{Deserialize, Serialize};
+use crate::{AppState, DockData, PaneData};
+use crate::dock_model::DockModel;
+use crate::theme::Theme;
+
+/// The command line options of the demo. They override the saved user settings.
+#[derive(Clone, Debug, Parser)]
+#[command(about = "A demo of a pane dock made with druid")]
+pub struct Cli {
+    /// How many panes the main dock starts with
+    #[arg(long, value_name = "COUNT", conflicts_with = "layout", value_parser = clap::value_parser!(i32).range(0..))]
+    pub panes: Option<i32>,
+    /// A JSON file with the panes the main dock starts with
+    #[arg(long, value_name = "FILE")]
+    pub layout: Option<PathBuf>,
+    /// A JSON file with the dock's colors, as hex strings
+    #[arg(long, value_name = "FILE")]
+    pub theme: Option<PathBuf>,
+    /// Open a pane with the title, as well as the others. Can be given more than once.
+    #[arg(long, value_name = "TITLE")]
+    pub open: Vec<String>,
+    /// Start with the dock hidden
+    #[arg(long, conflicts_with = "shown")]
+    pub hidden: bool,
+    /// Start with the dock shown
+    #[arg(long)]
+    pub shown: bool,
+    /// Keep the dock windows on top of other windows
+    #[arg(long)]
+    pub always_on_top: bool,
+    /// The size of the main window, and optionally where it goes, like 1024x600 or 1024x600+100+50
+    #[arg(long, value_name = "WxH[+X+Y]", value_parser = parse_geometry)]
+    pub geometry: Option<WindowGeometry>,
+    /// Print where the panes would be laid out as JSON, without opening a window
+    #[arg(long)]
+    pub headless_dump_layout: bool,
+    /// Listen on a Unix domain socket for JSON requests from other processes, like opening a pane.
+    /// If a dock is already listening there, the options are passed on to it instead.
+    #[cfg(unix)]
+    #[arg(long, value_name = "PATH")]
+    pub ipc_socket: Option<PathBuf>,
+    /// Pass the options on to the dock that is already running with this, instead of opening another one.
+    /// Without one running, this dock listens for the next launch, on a socket in the runtime directory.
+    #[cfg(unix)]
+    #[arg(long)]
+    pub single_instance: bool,
+}
+
+/// The size of a window, and where it goes if that's known.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowGeometry {
+    pub size: Size,
+    pub position: Option<Point>,
+}
+
+/// A file with the panes of a dock.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LayoutFile {
+    pub panes: Vec<PaneData>,
+    #[serde(default)]
+    pub maximized_pane: Option<i32>,
+}
+
+impl LayoutFile {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+/// Where a pane is laid out in the dock, for `--headless-dump-layout`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PaneRects {
+    pub id: i32,
+    /// As [x0, y0, x1, y1], relative to the dock
+    pub header: [f64; 4],
+    pub contents: [f64; 4],
+}
+
+/// Parses a geometry like 1024x600 or 1024x600+100+50.
+pub fn parse_geometry(geometry: &str) -> Result<WindowGeometry, String> {
+    let parse = |number: &str| number.parse::<f64>().map_err(|_| format!("{} isn't a number", number));
+    let mut parts = geometry.split('+');
+    let size = parts.next().unwrap_or_default();
+    let (width, height) = size.split_once('x')
+        .ok_or_else(|| format!("{} should be a size like 1024x600", size))?;
+    let size = Size::new(parse(width)?, parse(height)?);
+    let position = match (parts.next(), parts.next(), parts.next()) {
+        (None, _, _) => None,
+        (Some(x), Some(y), None) => Some(Point::new(parse(x)?, parse(y)?)),
+        _ => return Err(format!("{} should be a position like +100+50 after the size", geometry)),
+    };
+    Ok(WindowGeometry { size, position })
+}
+
+impl Cli {
+    /// Applies the options to the state the app starts with.
+    pub fn apply(&self, state: &mut AppState) -> io::Result<()> {
+        if let Some(count) = self.panes {
+            state.docks.set(0, DockData::new((0..count).map(PaneData::new).collect()));
+            state.next_pane_id = count;
+        }
+        if let Some(layout_path) = &self.layout {
+            let layout = LayoutFile::load(layout_path)?;
+            state.next_pane_id = layout.panes.iter().map(|pane| pane.id + 1).max().unwrap_or(0);
+            let mut dock = DockData::new(layout.panes.into_iter().collect());
+            dock.maximized_pane = layout.maximized_pane;
+            state.docks.set(0, dock);
+        }
+        self.apply_to_running(state)?;
+        if let Some(geometry) = self.geometry {
+            // Without a position, it stays where it was saved, or where the system puts it
+            let position = geometry.position.or(state.main_window_rect.map(|rect| rect.origin()));
+            state.main_window_rect = position.map(|position| Rect::from_origin_size(position, geometry.size));
+        }
+        Ok(())
+    }
+
+    /// Applies the options that make sense for a dock that is already running,
+    /// for when they're passed on by another launch. Returns the IDs of the panes it opened.
+    pub fn apply_to_running(&self, state: &mut AppState) -> io::Result<Vec<i32>> {
+        if let Some(theme_path) = &self.theme {
+            state.theme = Theme::load(theme_path)?;
+        }
+        if self.hidden || self.shown {
+            state.show_dock = self.shown;
+        }
+        if self.always_on_top {
+            state.always_on_top = true;
+        }
+        let opened_panes = self.open.iter()
+            .map(|title| state.add_titled_pane(0, Some(title.clone())))
+            .collect();
+        Ok(opened_panes)
+    }
+
+    /// Makes the file paths relative to `dir` instead of the working directory,
+    /// for the options of another launch, which may have been started somewhere else.
+    pub fn resolve_paths(&mut self, dir: &Path) {
+        for path in [&mut self.layout, &mut self.theme].into_iter().flatten() {
+            if path.is_relative() {
+                *path = dir.join(&*path);
+            }
+        }
+    }
+
+    /// The options that were given, which a dock that is already running doesn't take from another launch.
+    pub fn options_ignored_when_running(&self) -> Vec<&'static str> {
+        let given = [
+            ("--panes", self.panes.is_some()),
+            ("--layout", self.layout.is_some()),
+            ("--geometry", self.geometry.is_some()),
+        ];
+        given.into_iter().filter(|(_, is_given)| *is_given).map(|(option, _)| option).collect()
+    }
+
+    /// The size the main window opens with, when the state doesn't say where it goes.
+    pub fn window_size(&self) -> Option<Size> {
+        self.geometry.map(|geometry| geometry.size)
+    }
+}
+
+/// Lays out the panes of the dock in a window of the size, and returns where each one is.
+pub fn dump_layout(dock: &DockData, window_size: Size) -> Vec<PaneRects> {
+    let mut model = DockModel::new();
+    model.set_dock_size(window_size, Rect::ZERO);
+    model.sync(dock);
+    let to_array = |rect: Rect| [rect.x0, rect.y0, rect.x1, rect.y1];
+    dock.panes.iter().enumerate()
+        .map(|(index, pane)| PaneRects {
+            id: pane.id,
+            header: to_array(model.header_rect(index)),
+            contents: to_array(model.contents_rect(index)),
+        })
+        .collect()
+}
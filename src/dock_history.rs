@@ -0,0 +1,94 @@
+use druid::im;
+use crate::PaneData;
+
+// Oldest entries are dropped past this
+const MAX_HISTORY_LENGTH: usize = 100;
+
+/// The panes at one point in time. Their order and sizes make up the layout.
+pub type DockSnapshot = im::Vector<PaneData>;
+
+pub struct ClosedPane {
+    pub pane: PaneData,
+    // Index in the data when it was closed
+    pub index: usize,
+}
+
+pub struct DockHistory {
+    undo_stack: Vec<DockSnapshot>,
+    redo_stack: Vec<DockSnapshot>,
+    closed_panes: Vec<ClosedPane>,
+}
+
+impl Default for DockHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DockHistory {
+    pub fn new() -> Self {
+        Self {
+            undo_stack: vec![],
+            redo_stack: vec![],
+            closed_panes: vec![],
+        }
+    }
+
+    /// Call with the state from before a change is made.
+    /// This clears the redo stack, since it branches off from it.
+    pub fn record(&mut self, before: DockSnapshot) {
+        self.undo_stack.push(before);
+        if self.undo_stack.len() > MAX_HISTORY_LENGTH {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Returns the state to go back to, if there is one.
+    /// The current state is kept so that the undo can be redone.
+    pub fn undo(&mut self, current: DockSnapshot) -> Option<DockSnapshot> {
+        let previous = self.undo_stack.pop()?;
+        self.redo_stack.push(current);
+        Some(previous)
+    }
+
+    /// Returns the state to go forward to, if there is one.
+    pub fn redo(&mut self, current: DockSnapshot) -> Option<DockSnapshot> {
+        let next = self.redo_stack.pop()?;
+        self.undo_stack.push(current);
+        Some(next)
+    }
+
+    pub fn record_closed(&mut self, closed: ClosedPane) {
+        self.closed_panes.push(closed);
+        if self.closed_panes.len() > MAX_HISTORY_LENGTH {
+            self.closed_panes.remove(0);
+        }
+    }
+
+    pub fn take_last_closed(&mut self) -> Option<ClosedPane> {
+        self.closed_panes.pop()
+    }
+
+    /// Drops a pane that moved to another dock, so that undoing here can't bring back a copy of it.
+    pub fn forget_pane(&mut self, pane_id: i32) {
+        for snapshot in self.undo_stack.iter_mut().chain(self.redo_stack.iter_mut()) {
+            snapshot.retain(|pane| pane.id != pane_id);
+        }
+        self.closed_panes.retain(|closed| closed.pane.id != pane_id);
+    }
+
+    /// Adds a pane that moved in from another dock to every state, so that undoing here doesn't lose it.
+    pub fn adopt_pane(&mut self, pane: &PaneData) {
+        for snapshot in self.undo_stack.iter_mut().chain(self.redo_stack.iter_mut()) {
+            snapshot.push_back(pane.clone());
+        }
+    }
+
+    /// True if undoing, redoing, or reopening could bring back the pane.
+    pub fn contains_pane(&self, pane_id: i32) -> bool {
+        self.closed_panes.iter().any(|closed| closed.pane.id == pane_id)
+            || self.undo_stack.iter().chain(self.redo_stack.iter())
+                .any(|snapshot| snapshot.iter().any(|pane| pane.id == pane_id))
+    }
+}
@@ -0,0 +1,619 @@
+use std::time::Duration;
+use druid::{Point, Rect, Region, Size, Vec2};
+use tracing::trace;
+use crate::{AutoHide, DockData, HEADER_HEIGHT, PANE_SPACING, LOG_ANIMATION, LOG_DRAG};
+use crate::dock_history::DockSnapshot;
+use crate::drag_prediction::{DragPredictor, DragSettings};
+use crate::pane_layout::{self, HeaderButton, PaneTarget};
+
+/// Where a pane is on screen, for animating it into the place the data says it goes.
+/// Positions are relative to the bound side. Default dist from right.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaneAnimation {
+    pub pane_id: i32,
+    pub actual_x_pos: f64,
+    pub actual_width: f64,
+    pub actual_height: f64,
+    pub actual_y_offset: f64,
+}
+
+impl PaneAnimation {
+    fn new(pane_id: i32, target: &PaneTarget) -> Self {
+        Self {
+            pane_id,
+            actual_x_pos: target.x_pos,
+            actual_width: target.width,
+            actual_height: target.height,
+            actual_y_offset: target.y_offset,
+        }
+    }
+
+    fn snap_to(&mut self, target: &PaneTarget) {
+        self.actual_x_pos = target.x_pos;
+        self.actual_width = target.width;
+        self.actual_height = target.height;
+        self.actual_y_offset = target.y_offset;
+    }
+}
+
+/// The pane IDs that sync found to be added to or removed from the data.
+pub struct PaneChanges {
+    pub added: Vec<i32>,
+    pub removed: Vec<i32>,
+}
+
+impl PaneChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// How long the pointer needs to be away before an auto-hiding dock hides.
+pub const AUTO_HIDE_DELAY: Duration = Duration::from_secs(3);
+/// How much of the panes is left in view when they auto-hide to a strip.
+pub const AUTO_HIDE_STRIP_HEIGHT: f64 = 4.0;
+
+/// Where a pane dragged in from another dock would be dropped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DropPreview {
+    pub index: usize,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// The state of the dock that isn't widgets: where each pane is while it animates,
+/// and what the pointer is dragging. Positions given to it are relative to the dock.
+pub struct DockModel {
+    /// Order matches the order in the data, which is kept by sync.
+    panes: Vec<PaneAnimation>,
+    // The size from the last layout, for calculating the size of a maximized pane
+    dock_size: Size,
+    // Maximized panes stay below these, and they can always be clicked
+    persistent_items_rect: Rect,
+    // For dragging the dock and panes
+    init_pos: Option<Point>,
+    // By pane ID, since panes can be added, closed or moved from outside the dock while it's dragged
+    dragging_pane: Option<i32>,
+    drag_settings: DragSettings,
+    drag_predictor: DragPredictor,
+    // Where the pointer was pressed, and the header button it was pressed on
+    press_pos: Option<Point>,
+    pressed_button: Option<HeaderButton>,
+    // How long a pane header has been pressed without a drag starting, counted from the animation frames
+    press_duration: Duration,
+    // Set once the pointer moves past the drag threshold, or is held down on a header.
+    // Until then, nothing moves and releasing it is a click.
+    is_drag_started: bool,
+    // The panes when the current pane drag started, to be recorded if the pane moved
+    drag_start_panes: Option<DockSnapshot>,
+    // The position used to find where the dragged pane will be dropped
+    drop_x_pos: Option<f64>,
+    // The panes make room for it
+    drop_preview: Option<DropPreview>,
+    // Set when the dock auto-hides, and cleared when the pointer comes back
+    is_auto_hidden: bool,
+    // By pane ID. The focused pane is the one last pressed on, and the others can be dimmed.
+    focused_pane: Option<i32>,
+    // Panes the pointer isn't over are idle, and can be faded
+    hovered_pane: Option<i32>,
+    // Panes are snapped to their targets when they aren't being animated
+    is_animating: bool,
+}
+
+// Moves the actual value towards the target for one animation frame. Returns true if it moved.
+fn animate_towards(actual: &mut f64, target: f64, anim_speed_correction: f64) -> bool {
+    let min_animation_speed = 20.0 * anim_speed_correction;
+    let diff = target - *actual;
+    if diff.abs() > 0.0 {
+        let move_amount = if diff.abs() < min_animation_speed {
+            diff
+        } else {
+            let mut move_amount_magnitude = min_animation_speed;
+
+            move_amount_magnitude += diff.abs() * 0.15 * anim_speed_correction;
+            if diff.is_sign_negative() {
+                move_amount_magnitude *= -1.0;
+            }
+            move_amount_magnitude
+        };
+        *actual += move_amount;
+        true
+    } else {
+        false
+    }
+}
+
+impl Default for DockModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DockModel {
+    pub fn new() -> Self {
+        Self {
+            panes: vec![],
+            dock_size: Size::ZERO,
+            persistent_items_rect: Rect::ZERO,
+            init_pos: None,
+            dragging_pane: None,
+            drag_settings: DragSettings::default(),
+            drag_predictor: DragPredictor::new(DragSettings::default()),
+            press_pos: None,
+            pressed_button: None,
+            press_duration: Duration::ZERO,
+            is_drag_started: false,
+            drag_start_panes: None,
+            drop_x_pos: None,
+            drop_preview: None,
+            is_auto_hidden: false,
+            focused_pane: None,
+            hovered_pane: None,
+            is_animating: false,
+        }
+    }
+
+    pub fn panes(&self) -> &[PaneAnimation] {
+        &self.panes
+    }
+
+    pub fn dock_size(&self) -> Size {
+        self.dock_size
+    }
+
+    pub fn set_dock_size(&mut self, dock_size: Size, persistent_items_rect: Rect) {
+        self.dock_size = dock_size;
+        self.persistent_items_rect = persistent_items_rect;
+    }
+
+    /// The index of the pane being dragged, or pressed on before a drag starts.
+    pub fn dragging_pane(&self) -> Option<usize> {
+        let dragging_pane = self.dragging_pane?;
+        self.panes.iter().position(|pane| pane.pane_id == dragging_pane)
+    }
+
+    /// True once a pane drag has gone past the threshold, so it could be dropped into another dock.
+    pub fn is_pane_drag_started(&self) -> bool {
+        self.dragging_pane.is_some() && self.is_drag_started
+    }
+
+    pub fn drop_x_pos(&self) -> Option<f64> {
+        self.drop_x_pos
+    }
+
+    /// The index in the data that the dragged pane would be dropped at, if it were released now.
+    /// This is what the drag predictor finds, with its lead and hysteresis, so it's where the pane goes.
+    pub fn drop_target(&self, data: &DockData) -> Option<usize> {
+        let dragging_pane = self.dragging_pane()?;
+        let drop_x_pos = self.drop_x_pos?;
+        if data.maximized_pane.is_some() {
+            return Some(dragging_pane);
+        }
+        Some(self.drag_predictor.new_index(&self.targets(data), drop_x_pos, dragging_pane))
+    }
+
+    /// The smoothed velocity of the dragged pane, in pixels per pointer move
+    pub fn drag_velocity(&self) -> f64 {
+        self.drag_predictor.velocity()
+    }
+
+    pub fn drag_settings(&self) -> DragSettings {
+        self.drag_settings
+    }
+
+    /// Applies from the next pane drag
+    pub fn set_drag_settings(&mut self, drag_settings: DragSettings) {
+        self.drag_settings = drag_settings;
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.is_animating
+    }
+
+    pub fn start_animating(&mut self) {
+        self.is_animating = true;
+    }
+
+    /// Makes the panes match the panes in the data, in the same order.
+    /// Panes are matched by ID, so a pane that moves keeps its animation.
+    pub fn sync(&mut self, data: &DockData) -> PaneChanges {
+        let mut changes = PaneChanges { added: vec![], removed: vec![] };
+        let pane_ids_match = self.panes.iter().map(|pane| pane.pane_id)
+            .eq(data.panes.iter().map(|pane| pane.id));
+        if pane_ids_match {
+            return changes;
+        }
+
+        // New panes start out where they need to go. The other panes will animate to adjust.
+        let targets = self.targets(data);
+        let mut old_panes = std::mem::take(&mut self.panes);
+        for (pane_data, target) in data.panes.iter().zip(targets.iter()) {
+            let existing_index = old_panes.iter().position(|pane| pane.pane_id == pane_data.id);
+            let pane = match existing_index {
+                Some(existing_index) => old_panes.swap_remove(existing_index),
+                None => {
+                    changes.added.push(pane_data.id);
+                    PaneAnimation::new(pane_data.id, target)
+                }
+            };
+            self.panes.push(pane);
+        }
+        // Anything left is no longer in the data
+        changes.removed = old_panes.iter().map(|pane| pane.pane_id).collect();
+        // The dragged pane may have been closed from outside the dock
+        if self.dragging_pane.is_some() && self.dragging_pane().is_none() {
+            trace!(target: LOG_DRAG, "The dragged pane is gone, so the drag is dropped");
+            self.cancel_pointer();
+        }
+        changes
+    }
+
+    // The maximized pane fills the dock, below the persistent items
+    pub fn maximized_size(&self) -> Size {
+        let width = self.dock_size.width - PANE_SPACING * 2.0;
+        let height = self.dock_size.height - self.persistent_items_rect.height()
+            - HEADER_HEIGHT - PANE_SPACING;
+        Size::new(width.max(0.0), height.max(0.0))
+    }
+
+    pub fn targets(&self, data: &DockData) -> Vec<PaneTarget> {
+        let mut targets = pane_layout::target_layout(&data.panes, data.maximized_pane, self.maximized_size());
+        // Leave a gap where a pane from another dock would go
+        if let Some(drop_preview) = self.drop_preview {
+            for target in targets.iter_mut().skip(drop_preview.index) {
+                target.x_pos += drop_preview.width + PANE_SPACING;
+            }
+        }
+        if self.is_auto_hidden {
+            for target in targets.iter_mut() {
+                let hidden_y_offset = match data.auto_hide {
+                    AutoHide::Off => 0.0,
+                    AutoHide::ToStrip => target.height + HEADER_HEIGHT - AUTO_HIDE_STRIP_HEIGHT,
+                    AutoHide::ToHeaders => target.height,
+                };
+                target.y_offset = target.y_offset.max(hidden_y_offset);
+            }
+        }
+        targets
+    }
+
+    pub fn is_auto_hidden(&self) -> bool {
+        self.is_auto_hidden
+    }
+
+    /// Called once the pointer has been away for the auto-hide delay.
+    /// Returns true if the panes started sliding down.
+    pub fn hide_if_idle(&mut self, data: &DockData) -> bool {
+        // Not while the pointer is holding something
+        if data.auto_hide == AutoHide::Off || self.is_auto_hidden || self.init_pos.is_some() {
+            return false;
+        }
+        trace!(target: LOG_ANIMATION, "Auto-hiding the panes");
+        self.is_auto_hidden = true;
+        self.is_animating = true;
+        true
+    }
+
+    /// Called for any pointer event over the dock. Returns true if it brought the hidden panes back up.
+    pub fn pointer_activity(&mut self, pos: Point) -> bool {
+        if !self.is_auto_hidden || !self.input_area().contains(pos) {
+            return false;
+        }
+        self.reveal();
+        true
+    }
+
+    /// Brings auto-hidden panes back up.
+    pub fn reveal(&mut self) {
+        if self.is_auto_hidden {
+            trace!(target: LOG_ANIMATION, "Revealing the auto-hidden panes");
+            self.is_auto_hidden = false;
+            self.is_animating = true;
+        }
+    }
+
+    pub fn drop_preview(&self) -> Option<DropPreview> {
+        self.drop_preview
+    }
+
+    /// Returns true if the preview changed, and so the panes need to animate.
+    pub fn set_drop_preview(&mut self, drop_preview: Option<DropPreview>) -> bool {
+        if self.drop_preview == drop_preview {
+            return false;
+        }
+        self.drop_preview = drop_preview;
+        self.is_animating = true;
+        true
+    }
+
+    /// The index a pane from another dock would be inserted at, if it was dropped at the window position.
+    pub fn drop_index_at(&self, pos: Point, data: &DockData) -> usize {
+        // Without the gap, so that the gap moving doesn't change where it goes
+        let targets = pane_layout::target_layout(&data.panes, data.maximized_pane, self.maximized_size());
+        pane_layout::get_insert_index(&targets, self.from_window_x(pos.x))
+    }
+
+    /// Where the pane being previewed would be once dropped, in window positions.
+    pub fn drop_preview_rect(&self, data: &DockData) -> Option<Rect> {
+        let drop_preview = self.drop_preview?;
+        let targets = pane_layout::target_layout(&data.panes, data.maximized_pane, self.maximized_size());
+        let x_pos = match targets.get(drop_preview.index) {
+            Some(target) => target.x_pos,
+            None => targets.last().map_or(PANE_SPACING, |last| last.x_pos + last.width + PANE_SPACING),
+        };
+        Some(Rect::new(
+            self.to_window_x(x_pos + drop_preview.width),
+            self.dock_size.height - drop_preview.height - HEADER_HEIGHT,
+            self.to_window_x(x_pos),
+            self.dock_size.height,
+        ))
+    }
+
+    /// When not animating, the panes belong at their targets.
+    /// This keeps the maximized pane filling the dock when the window is resized.
+    pub fn snap_if_not_animating(&mut self, data: &DockData) {
+        if !self.is_animating {
+            let targets = self.targets(data);
+            for (pane, target) in self.panes.iter_mut().zip(targets.iter()) {
+                pane.snap_to(target);
+            }
+        }
+    }
+
+    // Converts a position relative to the bound side to a window position
+    pub fn to_window_x(&self, x_pos: f64) -> f64 {
+        self.dock_size.width - x_pos + 1.0
+    }
+
+    fn from_window_x(&self, window_x: f64) -> f64 {
+        self.dock_size.width - window_x + 1.0
+    }
+
+    /// The pane contents, aligned to the bottom and pushed down when minimized or hidden.
+    pub fn contents_rect(&self, index: usize) -> Rect {
+        let pane = &self.panes[index];
+        // Shift X by 1 for border
+        let origin = Point::new(
+            self.to_window_x(pane.actual_x_pos + pane.actual_width),
+            self.dock_size.height - pane.actual_height + pane.actual_y_offset,
+        );
+        Rect::from_origin_size(origin, Size::new(pane.actual_width, pane.actual_height))
+    }
+
+    /// The header, on top of the contents.
+    pub fn header_rect(&self, index: usize) -> Rect {
+        let contents_rect = self.contents_rect(index);
+        Rect::new(contents_rect.x0, contents_rect.y0 - HEADER_HEIGHT, contents_rect.x1, contents_rect.y0)
+    }
+
+    /// The parts of the window that take input when the dock is hidden.
+    pub fn input_area(&self) -> Region {
+        let mut interactable_area = Region::EMPTY;
+        interactable_area.add_rect(self.persistent_items_rect);
+        for index in 0..self.panes.len() {
+            interactable_area.add_rect(self.contents_rect(index));
+            interactable_area.add_rect(self.header_rect(index));
+        };
+        interactable_area
+    }
+
+    /// True if the pane isn't the one last pressed on. Until one is pressed, none are unfocused.
+    pub fn is_pane_unfocused(&self, pane_id: i32) -> bool {
+        matches!(self.focused_pane, Some(focused_pane) if focused_pane != pane_id)
+    }
+
+    pub fn focus_pane(&mut self, pane_id: i32) {
+        self.focused_pane = Some(pane_id);
+    }
+
+    /// True if the pointer isn't over the pane.
+    pub fn is_pane_idle(&self, pane_id: i32) -> bool {
+        self.hovered_pane != Some(pane_id)
+    }
+
+    /// Handles the pointer moving, whether or not anything is pressed.
+    /// Returns true if it moved onto or off a pane, which changes how they're painted.
+    pub fn pointer_hover(&mut self, pos: Option<Point>, data: &DockData) -> bool {
+        let hovered_pane = pos
+            .and_then(|pos| self.find_pane(pos))
+            .and_then(|index| data.panes.get(index))
+            .map(|pane| pane.id);
+        let changed = hovered_pane != self.hovered_pane;
+        self.hovered_pane = hovered_pane;
+        changed
+    }
+
+    pub fn find_pane(&self, point: Point) -> Option<usize> {
+        (0..self.panes.len()).find(|index| {
+            self.contents_rect(*index).contains(point) || self.header_rect(*index).contains(point)
+        })
+    }
+
+    fn header_button_at(&self, index: usize, point: Point) -> Option<HeaderButton> {
+        let header_rect = self.header_rect(index);
+        pane_layout::header_button_at(point - header_rect.origin().to_vec2(), header_rect.width())
+    }
+
+    /// Handles the left mouse button being pressed.
+    /// Returns true if the pointer is now dragging a pane or the window, and needs to be captured.
+    pub fn pointer_down(&mut self, pos: Point, count: u8, data: &mut DockData) -> bool {
+        self.cancel_pointer();
+        if !self.input_area().contains(pos) {
+            self.init_pos = Some(pos);
+            self.press_pos = Some(pos);
+            return true;
+        }
+        let pressed_pane = self.find_pane(pos);
+        if let Some(pane) = pressed_pane.and_then(|index| data.panes.get(index)) {
+            self.focused_pane = Some(pane.id);
+        }
+        // Now check to see if it's within a pane header
+        let index = match pressed_pane {
+            Some(index) if self.header_rect(index).contains(pos) => index,
+            _ => return false,
+        };
+        let pressed_button = self.header_button_at(index, pos);
+        if count == 2 && pressed_button.is_none() {
+            // Double clicking the header toggles maximized
+            data.toggle_maximized(index);
+            return false;
+        }
+        self.drag_start_panes = Some(data.panes.clone());
+        self.init_pos = Some(pos);
+        self.press_pos = Some(pos);
+        self.pressed_button = pressed_button;
+        self.dragging_pane = Some(self.panes[index].pane_id);
+        self.drag_predictor = DragPredictor::new(self.drag_settings);
+        trace!(target: LOG_DRAG, "Pressed the header of the pane at index {}", index);
+        // Start animating
+        self.is_animating = true;
+        true
+    }
+
+    /// Handles the pointer moving while captured.
+    /// Returns how far to move the window if the window is being dragged.
+    pub fn pointer_move(&mut self, pos: Point, data: &mut DockData) -> Option<Vec2> {
+        let init_pos = self.init_pos?;
+        if !self.is_drag_started {
+            let press_pos = self.press_pos.unwrap_or(init_pos);
+            if (pos - press_pos).hypot() < self.drag_settings.drag_threshold {
+                return None;
+            }
+            trace!(target: LOG_DRAG, "Started dragging, since the pointer moved past the threshold");
+            self.is_drag_started = true;
+        }
+        // From where the pointer was pressed for the first move of the drag
+        let within_window_change = pos.to_vec2() - init_pos.to_vec2();
+        // Could either be dragging a pane or the entire window
+        let dragging_pane = match self.dragging_pane() {
+            Some(dragging_pane) => dragging_pane,
+            None => return Some(within_window_change),
+        };
+        if data.maximized_pane.is_some() {
+            // The maximized pane fills the dock, so there is nowhere to move it
+            return None;
+        }
+
+        // Move the pane's actual position based on the change
+        let pane = &mut self.panes[dragging_pane];
+        pane.actual_x_pos -= within_window_change.x;
+        let actual_x_pos = pane.actual_x_pos;
+        self.drag_predictor.add_movement(-within_window_change.x);
+
+        // See if there is a new place in the order for this pane
+        // If so, move it in the data. The rest will animate to adjust.
+        let width = data.panes[dragging_pane].width;
+        let drop_x_pos = self.drag_predictor.drop_x_pos(actual_x_pos, width);
+        self.drop_x_pos = Some(drop_x_pos);
+        let targets = self.targets(data);
+        let new_index = self.drag_predictor.new_index(&targets, drop_x_pos, dragging_pane);
+        if new_index != dragging_pane {
+            // Keep the panes in the same order as the data
+            let pane_data = data.panes.remove(dragging_pane);
+            data.panes.insert(new_index, pane_data);
+            let pane = self.panes.remove(dragging_pane);
+            self.panes.insert(new_index, pane);
+            trace!(target: LOG_DRAG, "Moved dragged pane from index {} to {}", dragging_pane, new_index);
+        }
+
+        self.init_pos = Some(pos); // Move it to prevent compounding changes
+        None
+    }
+
+    /// Handles the left mouse button being released while captured.
+    /// Pressing and releasing on the same header button clicks it, as long as a drag didn't start in between.
+    /// Returns the panes from before the drag if a pane drag changed the layout, for the history.
+    pub fn pointer_up(&mut self, pos: Point, data: &mut DockData) -> Option<DockSnapshot> {
+        let mut finished_drag = None;
+        if let Some(dragging_pane) = self.dragging_pane() {
+            // Check to see if it was on the header button it was pressed on
+            let clicked_button = if self.is_drag_started {
+                None
+            } else {
+                self.header_button_at(dragging_pane, pos).filter(|button| Some(*button) == self.pressed_button)
+            };
+
+            match clicked_button {
+                Some(HeaderButton::Close) => {
+                    data.close_pane(dragging_pane);
+                }
+                Some(HeaderButton::Maximize) => {
+                    data.toggle_maximized(dragging_pane);
+                }
+                Some(HeaderButton::Minimize) => {
+                    data.toggle_minimized(dragging_pane);
+                }
+                None => {
+                    // Finalize the position
+                    if let Some(drag_start_panes) = self.drag_start_panes.take() {
+                        if pane_layout::layout_changed(&drag_start_panes, &data.panes) {
+                            trace!(target: LOG_DRAG, "Finished dragging pane to index {}", dragging_pane);
+                            finished_drag = Some(drag_start_panes);
+                        }
+                    }
+                }
+            }
+            // The dragged pane animates into place
+            self.is_animating = true;
+        }
+        self.cancel_pointer();
+        finished_drag
+    }
+
+    /// Drops whatever the pointer was dragging, without clicking or recording anything.
+    pub fn cancel_pointer(&mut self) {
+        self.drag_start_panes = None;
+        self.drop_x_pos = None;
+        self.init_pos = None;
+        self.dragging_pane = None;
+        self.press_pos = None;
+        self.pressed_button = None;
+        self.press_duration = Duration::ZERO;
+        self.is_drag_started = false;
+    }
+
+    /// Moves the panes towards their targets for one animation frame.
+    /// Returns true if any pane moved, and so needs to be laid out again.
+    pub fn animate(&mut self, time_since_last_frame: u64, data: &DockData) -> bool {
+        // Check if any panes need animating. If they do, is_animating stays set
+        let expected_time = 16000000.0;
+        let time_relative_to_reference = time_since_last_frame as f64 / expected_time;
+        let anim_speed_correction = time_relative_to_reference.max(0.25).min(1.25);
+        // Frames keep coming while a header is pressed, so they also time holding it down
+        if self.dragging_pane.is_some() && !self.is_drag_started {
+            self.press_duration += Duration::from_nanos(time_since_last_frame);
+            if self.press_duration >= self.drag_settings.hold_duration {
+                trace!(target: LOG_DRAG, "Started dragging, since the header was held down");
+                self.is_drag_started = true;
+            }
+        }
+        let mut animation_needed = false;
+        let mut layout_needed = false;
+        let targets = self.targets(data);
+        for (pane, target) in self.panes.iter_mut().zip(targets.iter()) {
+            // Skip the dragged pane's x position until it's no longer being dragged
+            let is_dragged = self.dragging_pane == Some(pane.pane_id);
+            if !is_dragged {
+                layout_needed |= animate_towards(&mut pane.actual_x_pos, target.x_pos, anim_speed_correction);
+            }
+            layout_needed |= animate_towards(&mut pane.actual_width, target.width, anim_speed_correction);
+            layout_needed |= animate_towards(&mut pane.actual_height, target.height, anim_speed_correction);
+            layout_needed |= animate_towards(&mut pane.actual_y_offset, target.y_offset, anim_speed_correction);
+
+            // Now determine if more change is required
+            if (!is_dragged && pane.actual_x_pos != target.x_pos)
+                || pane.actual_width != target.width
+                || pane.actual_height != target.height
+                || pane.actual_y_offset != target.y_offset {
+                animation_needed = true;
+            }
+        }
+        // No changes may be needed except for the currently dragged pane,
+        // so keep animating until dragging is complete.
+        if self.is_animating && !animation_needed && self.dragging_pane.is_none() {
+            trace!(target: LOG_ANIMATION, "All panes are in place");
+        }
+        self.is_animating = animation_needed || self.dragging_pane.is_some();
+        layout_needed
+    }
+}
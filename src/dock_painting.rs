@@ -0,0 +1,156 @@
+use druid::{Color, Env, Key, Rect, RenderContext, Size};
+use crate::dock_model::DockModel;
+use crate::window_resizing::GRIP_SIZE;
+use crate::{DockData, PaneData};
+use crate::{DOCK_BACKGROUND_COLOR, PANE_BACKGROUND_COLOR, PANE_HEADER_COLOR};
+use crate::{DOCK_BACKGROUND_COLOR_KEY, PANE_BACKGROUND_COLOR_KEY, PANE_HEADER_COLOR_KEY};
+use crate::{DIM_UNFOCUSED_PANES_KEY, IDLE_PANE_OPACITY_KEY, PANE_HEADER_BUTTON_COLOR_KEY};
+
+const BORDER_COLOR: Color = Color::rgba8(0, 0, 0, 100);
+const DROP_PREVIEW_COLOR: Color = Color::rgba8(255, 255, 255, 60);
+const UNFOCUSED_DIMMING_COLOR: Color = Color::rgba8(0, 0, 0, 90);
+const GRIP_COLOR: Color = Color::rgba8(255, 255, 255, 30);
+// The colors that the pane widgets paint with, which fade with the pane.
+// Piet can't paint a group with an opacity, so each color is faded instead.
+const PANE_WIDGET_COLOR_KEYS: [Key<Color>; 3] = [
+    druid::theme::TEXT_COLOR,
+    druid::theme::SCROLLBAR_COLOR,
+    PANE_HEADER_BUTTON_COLOR_KEY,
+];
+
+/// The colors that the dock paints itself with, and how see-through the panes are.
+#[derive(Clone, Debug)]
+pub struct DockColors {
+    pub dock_background: Color,
+    pub pane_background: Color,
+    pub pane_header: Color,
+    pub idle_pane_opacity: f64,
+    pub dim_unfocused_panes: bool,
+}
+
+impl DockColors {
+    pub fn from_env(env: &Env) -> Self {
+        Self {
+            dock_background: env.get(DOCK_BACKGROUND_COLOR_KEY),
+            pane_background: env.get(PANE_BACKGROUND_COLOR_KEY),
+            pane_header: env.get(PANE_HEADER_COLOR_KEY),
+            idle_pane_opacity: env.get(IDLE_PANE_OPACITY_KEY),
+            dim_unfocused_panes: env.get(DIM_UNFOCUSED_PANES_KEY),
+        }
+    }
+
+    /// How opaque a pane is, which is less if the pointer isn't over it.
+    pub fn pane_opacity(&self, is_idle: bool) -> f64 {
+        if is_idle { self.idle_pane_opacity } else { 1.0 }
+    }
+
+    /// The colors for a pane, which are faded if the pointer isn't over it.
+    pub fn for_pane(&self, is_idle: bool) -> Self {
+        let opacity = self.pane_opacity(is_idle);
+        Self {
+            pane_background: with_opacity(&self.pane_background, opacity),
+            pane_header: with_opacity(&self.pane_header, opacity),
+            ..self.clone()
+        }
+    }
+}
+
+fn with_opacity(color: &Color, opacity: f64) -> Color {
+    let (red, green, blue, alpha) = color.as_rgba();
+    Color::rgba(red, green, blue, alpha * opacity)
+}
+
+/// The env for the widgets of a pane with the opacity, so that their text, scrollbars and buttons fade with it.
+pub fn pane_env(env: &Env, opacity: f64) -> Env {
+    let mut pane_env = env.clone();
+    for key in &PANE_WIDGET_COLOR_KEYS {
+        pane_env.set(key, with_opacity(&env.get(key), opacity));
+    }
+    pane_env
+}
+
+impl Default for DockColors {
+    fn default() -> Self {
+        Self {
+            dock_background: DOCK_BACKGROUND_COLOR,
+            pane_background: PANE_BACKGROUND_COLOR,
+            pane_header: PANE_HEADER_COLOR,
+            idle_pane_opacity: 1.0,
+            dim_unfocused_panes: false,
+        }
+    }
+}
+
+/// Paints what goes under a pane's widgets.
+pub fn paint_pane_background(rc: &mut impl RenderContext, header_rect: Rect, contents_rect: Rect, colors: &DockColors) {
+    rc.fill(contents_rect, &colors.pane_background);
+    rc.fill(header_rect, &colors.pane_header);
+}
+
+/// Paints what goes over a pane's widgets.
+pub fn paint_pane_border(rc: &mut impl RenderContext, header_rect: Rect, contents_rect: Rect) {
+    let left = contents_rect.x0.min(header_rect.x0);
+    let right = contents_rect.x1.min(header_rect.x1);
+    let top = header_rect.y0;
+    let bottom = contents_rect.y1;
+    rc.fill(Rect::new(left - 1.0, top, left, bottom), &BORDER_COLOR);
+    rc.fill(Rect::new(right, top, right + 1.0, bottom), &BORDER_COLOR);
+}
+
+/// Darkens a pane that isn't focused, over its widgets.
+pub fn paint_pane_dimming(rc: &mut impl RenderContext, header_rect: Rect, contents_rect: Rect) {
+    rc.fill(header_rect.union(contents_rect), &UNFOCUSED_DIMMING_COLOR);
+}
+
+/// Paints where a pane dragged in from another dock would go.
+pub fn paint_drop_preview(rc: &mut impl RenderContext, preview_rect: Rect) {
+    rc.fill(preview_rect, &DROP_PREVIEW_COLOR);
+    rc.stroke(preview_rect.inset(-1.0), &Color::WHITE, 1.0);
+}
+
+/// What the panes of a dock are painted on. The dock paints on its window, with the pane widgets,
+/// and the snapshot tests paint on a bitmap without them.
+pub trait DockCanvas {
+    type Render: RenderContext;
+
+    fn render_ctx(&mut self) -> &mut Self::Render;
+
+    /// Paints the widgets of the pane, over its background and under its border, faded to the opacity.
+    fn paint_pane_widgets(&mut self, pane: &PaneData, opacity: f64);
+}
+
+/// Paints the panes of the dock where the model has them, and where a pane from another dock would be dropped.
+pub fn paint_panes(canvas: &mut impl DockCanvas, model: &DockModel, dock: &DockData, colors: &DockColors) {
+    // Until the model is synced, it can have fewer panes than the data
+    for (index, pane) in dock.panes.iter().enumerate().take(model.panes().len()) {
+        let header_rect = model.header_rect(index);
+        let contents_rect = model.contents_rect(index);
+        let is_idle = model.is_pane_idle(pane.id);
+        paint_pane_background(canvas.render_ctx(), header_rect, contents_rect, &colors.for_pane(is_idle));
+        canvas.paint_pane_widgets(pane, colors.pane_opacity(is_idle));
+        paint_pane_border(canvas.render_ctx(), header_rect, contents_rect);
+        if colors.dim_unfocused_panes && model.is_pane_unfocused(pane.id) {
+            paint_pane_dimming(canvas.render_ctx(), header_rect, contents_rect);
+        }
+    }
+
+    if let Some(preview_rect) = model.drop_preview_rect(dock) {
+        paint_drop_preview(canvas.render_ctx(), preview_rect);
+    }
+}
+
+/// Paints the background of the dock window where it's being repainted.
+/// The window is transparent, so what was painted before is cleared first,
+/// or it would show through where the dock was hidden or the panes moved away.
+pub fn paint_window_background(rc: &mut impl RenderContext, paint_area: Rect, show_dock: bool, colors: &DockColors) {
+    rc.clear(paint_area, Color::TRANSPARENT);
+    if show_dock {
+        rc.fill(paint_area, &colors.dock_background);
+    }
+}
+
+/// Shows where the window can be resized from, when there is no dock background to show it.
+pub fn paint_resize_border(rc: &mut impl RenderContext, window_size: Size) {
+    let grip_border = window_size.to_rect().inset(-GRIP_SIZE / 2.0);
+    rc.stroke(grip_border, &GRIP_COLOR, GRIP_SIZE);
+}
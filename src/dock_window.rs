@@ -0,0 +1,553 @@
+use druid::widget::{Flex, Label, Button, Checkbox, Container, EnvScope, LineBreaking, Slider};
+use druid::widget::prelude::*;
+use druid::{WidgetPod, WidgetExt, WindowDesc, Point, Color, Cursor, LensExt, Rect, Region, Screen, Selector};
+use tracing::trace;
+use crate::{AppState, Appearance, DockData, DEFAULT_PANE_HEIGHT, DEFAULT_PANE_WIDTH, HEADER_HEIGHT, PANE_SPACING, LOG_LAYOUT, LOG_WINDOW};
+use crate::{DOCK_BACKGROUND_COLOR_KEY, PANE_BACKGROUND_COLOR_KEY, PANE_HEADER_COLOR_KEY};
+use crate::{PANE_HEADER_BUTTON_COLOR, PANE_HEADER_BUTTON_COLOR_KEY};
+use crate::{DIM_UNFOCUSED_PANES_KEY, IDLE_PANE_OPACITY_KEY};
+use crate::dock_painting::{self, DockColors};
+use crate::user_settings;
+use crate::window_resizing::{self, ResizeGrip};
+use crate::pane_dock_widget::{PaneDockWidget, UNDO, REDO, REOPEN_CLOSED_PANE, TOGGLE_DEBUG_OVERLAY, TOGGLE_RECORDING, REPLAY_RECORDING};
+
+/// Adds a pane to the dock in the window that was used last.
+pub const ADD_PANE: Selector = Selector::new("polysoft.druid-demo.add-pane");
+/// Adds another dock to the window, next to the others.
+pub const SPLIT_DOCK: Selector = Selector::new("polysoft.druid-demo.split-dock");
+/// Changes how the dock in the window that was used last auto-hides, going through each mode in turn.
+pub const CYCLE_AUTO_HIDE: Selector = Selector::new("polysoft.druid-demo.cycle-auto-hide");
+/// Changes how see-through the dock and its panes are, in every dock window.
+pub const SET_APPEARANCE: Selector<Appearance> = Selector::new("polysoft.druid-demo.set-appearance");
+
+// Closes the window, after noting where it was
+const CLOSE_DOCK_WINDOW: Selector = Selector::new("polysoft.druid-demo.close-dock-window");
+
+// These act on a single dock, so they only go to the one used last
+const DOCK_COMMANDS: [Selector; 6] = [UNDO, REDO, REOPEN_CLOSED_PANE, TOGGLE_DEBUG_OVERLAY, TOGGLE_RECORDING, REPLAY_RECORDING];
+
+/// The size dock windows open with, unless the main window's place was saved.
+pub const DEFAULT_WINDOW_SIZE: Size = Size::new(1024.0, DEFAULT_PANE_HEIGHT + HEADER_HEIGHT + 100.0);
+
+/// A window with a dock for the dock at the index in the data.
+pub fn dock_window(dock_index: usize) -> WindowDesc<AppState> {
+    let root = EnvScope::new(
+        |env: &mut druid::env::Env, data: &AppState| {
+            env.set(DOCK_BACKGROUND_COLOR_KEY, data.theme.dock_background.with_alpha(data.appearance.background_opacity));
+            env.set(PANE_BACKGROUND_COLOR_KEY, data.theme.pane_background);
+            env.set(PANE_HEADER_COLOR_KEY, data.theme.pane_header);
+            env.set(PANE_HEADER_BUTTON_COLOR_KEY, PANE_HEADER_BUTTON_COLOR);
+            env.set(druid::theme::SCROLLBAR_COLOR, data.theme.pane_scrollbar);
+            env.set(druid::theme::SCROLLBAR_BORDER_COLOR, Color::TRANSPARENT);
+            env.set(IDLE_PANE_OPACITY_KEY, data.appearance.idle_pane_opacity);
+            env.set(DIM_UNFOCUSED_PANES_KEY, data.appearance.dim_unfocused_panes);
+        },
+        DockWindow::new().with_dock(dock_index, 1.0)
+    );
+    let title = if dock_index == 0 {
+        "Pane Demo Main Win".to_string()
+    } else {
+        format!("Pane Demo Dock {}", dock_index + 1)
+    };
+    let mut window = WindowDesc::new(root)
+        .title(title)
+        .window_size(DEFAULT_WINDOW_SIZE)
+        .transparent(true);
+    if cfg!(target_os = "macos") {
+        window = window.show_titlebar(false);
+    }
+    window
+}
+
+// Sliders for the background and idle pane opacity, and a checkbox for dimming unfocused panes
+fn appearance_controls() -> Flex<AppState> {
+    let background_opacity = AppState::appearance.then(Appearance::background_opacity);
+    let idle_pane_opacity = AppState::appearance.then(Appearance::idle_pane_opacity);
+    let dim_unfocused_panes = AppState::appearance.then(Appearance::dim_unfocused_panes);
+    Flex::column()
+        .with_child(Flex::row()
+            .with_child(Label::new("Background"))
+            .with_child(Slider::new().with_range(0.0, 1.0).lens(background_opacity)))
+        .with_child(Flex::row()
+            .with_child(Label::new("Idle Panes"))
+            .with_child(Slider::new().with_range(0.0, 1.0).lens(idle_pane_opacity)))
+        .with_child(Checkbox::new("Dim Unfocused Panes").lens(dim_unfocused_panes))
+}
+
+/// The root of a dock window. It has the window's controls, the background when the dock is shown,
+/// and the input region when it's hidden. The docks in it are placed side by side.
+pub struct DockWindow {
+    dock_items: WidgetPod<AppState, Flex<AppState>>,
+    persistent_items: WidgetPod<AppState, Container<AppState>>,
+    docks: Vec<PlacedDock>,
+    // The dock that commands like undo and adding a pane go to, which is the one last pressed on
+    current_dock: usize,
+    // The rects last given to set_input_region, or None if the whole window takes input.
+    // It's only set again when they change.
+    input_rects: Option<Vec<Rect>>,
+    // Where the window was before overlay mode made it fill the work area, to put it back after
+    windowed_geometry: Option<Rect>,
+    // Set while a resize grip is being dragged
+    resize: Option<WindowResize>,
+}
+
+struct WindowResize {
+    grip: ResizeGrip,
+    // In screen coordinates, from when the grip was pressed
+    start_window_rect: Rect,
+    start_pointer: Point,
+}
+
+struct PlacedDock {
+    // The index of the dock in the data
+    dock_index: usize,
+    // How much of the window's width it gets, compared to the other docks
+    flex: f64,
+    pane_dock: WidgetPod<DockData, PaneDockWidget>,
+}
+
+impl PlacedDock {
+    fn new(dock_index: usize, flex: f64) -> Self {
+        Self {
+            dock_index,
+            flex,
+            pane_dock: WidgetPod::new(PaneDockWidget::new()),
+        }
+    }
+}
+
+impl Default for DockWindow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DockWindow {
+    pub fn new() -> Self {
+        let info_label = Label::new("Move and resize the pane dock, then hide the dock.");
+
+        let toggle_dock_button = Button::new("Toggle Dock")
+            .on_click(|ctx, is_shown: &mut bool, _: &Env| {
+                // Every dock window follows it in update
+                *is_shown = !*is_shown;
+                ctx.request_layout();
+            })
+            .lens(AppState::show_dock)
+            .boxed();
+
+        let overlay_mode_button = Button::new("Toggle Overlay")
+            .on_click(|ctx, is_overlay: &mut bool, _: &Env| {
+                // Every dock window follows it in update
+                *is_overlay = !*is_overlay;
+                ctx.request_layout();
+            })
+            .lens(AppState::overlay_mode);
+
+        let add_pane_button = Button::new("Add Pane")
+            .on_click(|ctx, _: &mut AppState, _: &Env| {
+                ctx.submit_command(ADD_PANE);
+            });
+
+        let mut dock_items = Flex::row()
+            .with_child(info_label);
+        if cfg!(target_os = "linux") {
+            // Can't manually set this on Linux, but it is more often than not an option on the titlebar
+            let mut always_on_top_msg: Label<AppState> = Label::new("If the always on top button does nothing, most desktop environments allow you to set this window always on top by right clicking on the titlebar and selecting \"Always on top\"");
+            always_on_top_msg.set_line_break_mode(LineBreaking::WordWrap);
+            dock_items.add_default_spacer();
+            dock_items.add_child(always_on_top_msg);
+        }
+        let always_on_top_button = Button::new("Toggle Always On Top")
+            .on_click(|_, data: &mut AppState, _: &Env| {
+                // Every dock window follows it in update
+                data.always_on_top = !data.always_on_top;
+            });
+        let new_dock_window_button = Button::new("New Dock Window")
+            .on_click(|ctx, data: &mut AppState, _: &Env| {
+                let dock_index = data.add_dock();
+                trace!(target: LOG_WINDOW, "Opening a window for dock {}", dock_index);
+                ctx.new_window(dock_window(dock_index));
+            });
+        let split_dock_button = Button::new("Split Dock")
+            .on_click(|ctx, _: &mut AppState, _: &Env| {
+                ctx.submit_command(SPLIT_DOCK);
+            });
+        let auto_hide_button = Button::new("Cycle Auto-Hide")
+            .on_click(|ctx, _: &mut AppState, _: &Env| {
+                ctx.submit_command(CYCLE_AUTO_HIDE);
+            });
+        let reopen_pane_button = Button::new("Reopen Closed Pane")
+            .on_click(|ctx, _: &mut AppState, _: &Env| {
+                ctx.submit_command(REOPEN_CLOSED_PANE);
+            });
+        let close_window_button = Button::new("Close Window")
+            .on_click(|ctx, _: &mut AppState, _| {
+                ctx.submit_command(CLOSE_DOCK_WINDOW.to(ctx.window_id()));
+            });
+        dock_items.add_child(reopen_pane_button);
+        dock_items.add_child(close_window_button);
+        dock_items.add_child(always_on_top_button);
+        dock_items.add_child(new_dock_window_button);
+        dock_items.add_child(split_dock_button);
+        dock_items.add_child(auto_hide_button);
+        dock_items.add_child(appearance_controls());
+
+        let persistent_items = Flex::column()
+            .with_child(toggle_dock_button)
+            .with_child(add_pane_button)
+            .with_child(overlay_mode_button)
+            .padding(3.0)
+            .background(Color::rgba(255.0, 255.0, 255.0, 0.1));
+
+        Self {
+            dock_items: WidgetPod::new(dock_items),
+            persistent_items: WidgetPod::new(persistent_items),
+            docks: vec![],
+            current_dock: 0,
+            input_rects: None,
+            windowed_geometry: None,
+            resize: None,
+        }
+    }
+
+    /// Adds the dock at the index in the data, with a share of the window's width.
+    pub fn with_dock(mut self, dock_index: usize, flex: f64) -> Self {
+        self.docks.push(PlacedDock::new(dock_index, flex));
+        self
+    }
+
+    fn handle_command(&mut self, ctx: &mut druid::EventCtx, command: &druid::Command, data: &mut AppState) {
+        if command.is(ADD_PANE) {
+            if let Some(placed) = self.docks.get(self.current_dock) {
+                data.add_pane(placed.dock_index);
+            }
+            ctx.set_handled();
+        } else if command.is(CYCLE_AUTO_HIDE) {
+            if let Some(placed) = self.docks.get(self.current_dock) {
+                let dock = &mut data.docks[placed.dock_index];
+                dock.auto_hide = dock.auto_hide.next();
+                trace!(target: LOG_WINDOW, "Dock {} now auto-hides {:?}", placed.dock_index, dock.auto_hide);
+            }
+            ctx.set_handled();
+        } else if let Some(appearance) = command.get(SET_APPEARANCE) {
+            data.appearance = *appearance;
+            ctx.set_handled();
+        } else if command.is(CLOSE_DOCK_WINDOW) {
+            self.record_window_rect(ctx.window(), data);
+            ctx.window().close();
+            ctx.set_handled();
+        } else if command.is(SPLIT_DOCK) {
+            let dock_index = data.add_dock();
+            trace!(target: LOG_WINDOW, "Adding dock {} to the window", dock_index);
+            self.docks.push(PlacedDock::new(dock_index, 1.0));
+            self.current_dock = self.docks.len() - 1;
+            ctx.children_changed();
+            ctx.set_handled();
+        }
+    }
+
+    // The main window has the first dock, and is the one whose place is saved
+    fn is_main_window(&self) -> bool {
+        self.docks.iter().any(|placed| placed.dock_index == 0)
+    }
+
+    // Notes where the main window is, or where it goes back to if it's in overlay mode
+    fn record_window_rect(&self, window: &druid::WindowHandle, data: &mut AppState) {
+        if !self.is_main_window() {
+            return;
+        }
+        let window_rect = self.windowed_geometry
+            .unwrap_or_else(|| Rect::from_origin_size(window.get_position(), window.get_size()));
+        if data.main_window_rect != Some(window_rect) {
+            data.main_window_rect = Some(window_rect);
+        }
+    }
+
+    // Applies the settings the window didn't open with, and keeps the main window on the screen,
+    // since the monitors may have changed since its place was saved
+    fn restore_window(&mut self, window: &druid::WindowHandle, data: &mut AppState) {
+        if data.always_on_top {
+            window.set_always_on_top(true);
+        }
+        if !data.show_dock {
+            window.show_titlebar(false);
+        }
+        if !self.is_main_window() {
+            return;
+        }
+        let mut monitors = Screen::get_monitors();
+        // The primary monitor is used if the window isn't on any of them
+        monitors.sort_by_key(|monitor| !monitor.is_primary());
+        let work_areas: Vec<Rect> = monitors.iter().map(|monitor| monitor.virtual_work_rect()).collect();
+        let restored_rect = data.main_window_rect
+            .and_then(|saved_rect| user_settings::clamp_to_work_areas(saved_rect, &work_areas));
+        match restored_rect {
+            Some(window_rect) => {
+                trace!(target: LOG_WINDOW, "Restoring the main window to {:?}, saved as {:?}", window_rect, data.main_window_rect);
+                window.set_position(window_rect.origin());
+                window.set_size(window_rect.size());
+                data.main_window_rect = Some(window_rect);
+            }
+            None => self.record_window_rect(window, data),
+        }
+    }
+
+    // Makes the window fill the work area of the monitor it's on, or puts it back where it was
+    fn apply_overlay_mode(&mut self, window: &druid::WindowHandle, data: &AppState) {
+        if data.overlay_mode {
+            let window_rect = Rect::from_origin_size(window.get_position(), window.get_size());
+            let monitors = Screen::get_monitors();
+            let monitor = monitors.iter()
+                .find(|monitor| monitor.virtual_rect().contains(window_rect.center()))
+                .or_else(|| monitors.iter().find(|monitor| monitor.is_primary()));
+            let work_area = match monitor {
+                Some(monitor) => monitor.virtual_work_rect(),
+                None => return,
+            };
+            trace!(target: LOG_WINDOW, "Entering overlay mode over {:?}", work_area);
+            self.windowed_geometry = Some(window_rect);
+            window.show_titlebar(false);
+            window.set_position(work_area.origin());
+            window.set_size(work_area.size());
+        } else if let Some(windowed_geometry) = self.windowed_geometry.take() {
+            trace!(target: LOG_WINDOW, "Leaving overlay mode, back to {:?}", windowed_geometry);
+            window.show_titlebar(data.show_dock);
+            window.set_position(windowed_geometry.origin());
+            window.set_size(windowed_geometry.size());
+        }
+    }
+
+    // Without a titlebar, the window can only be resized with the grips on its edges.
+    // In overlay mode it fills the work area, so it isn't resized.
+    fn can_resize(&self, data: &AppState) -> bool {
+        !data.overlay_mode && (!data.show_dock || cfg!(target_os = "macos"))
+    }
+
+    // Big enough for the persistent items, and the biggest pane in each dock
+    fn min_window_size(&self, data: &AppState) -> Size {
+        let panes = self.docks.iter().flat_map(|placed| data.docks[placed.dock_index].panes.iter());
+        let (widest, tallest) = panes.fold((0.0, 0.0), |(widest, tallest): (f64, f64), pane| {
+            (widest.max(pane.width), tallest.max(pane.height))
+        });
+        let pane_size = if widest > 0.0 { Size::new(widest, tallest) } else { Size::new(DEFAULT_PANE_WIDTH, DEFAULT_PANE_HEIGHT) };
+        let persistent_items_size = self.persistent_items.layout_rect().size();
+        Size::new(
+            persistent_items_size.width.max((pane_size.width + PANE_SPACING) * self.docks.len() as f64),
+            persistent_items_size.height + HEADER_HEIGHT + pane_size.height,
+        )
+    }
+
+    // Returns true if the event was for a resize grip, and shouldn't go to the docks
+    fn handle_resizing(&mut self, ctx: &mut druid::EventCtx, event: &druid::Event, data: &mut AppState) -> bool {
+        match event {
+            Event::MouseDown(me) if me.buttons.has_left() && self.can_resize(data) => {
+                let grip = match window_resizing::grip_at(me.pos, ctx.size()) {
+                    Some(grip) => grip,
+                    None => return false,
+                };
+                let window_pos = ctx.window().get_position();
+                trace!(target: LOG_WINDOW, "Started resizing the window with {:?}", grip);
+                self.resize = Some(WindowResize {
+                    grip,
+                    start_window_rect: Rect::from_origin_size(window_pos, ctx.window().get_size()),
+                    start_pointer: window_pos + me.pos.to_vec2(),
+                });
+                ctx.set_active(true);
+                ctx.set_handled();
+                true
+            }
+            Event::MouseMove(me) => {
+                if let Some(resize) = &self.resize {
+                    // The window moves when resized from the left or top, so the pointer is found on the screen
+                    let pointer = ctx.window().get_position() + me.pos.to_vec2();
+                    let window_rect = window_resizing::resize(resize.start_window_rect, resize.grip,
+                        pointer - resize.start_pointer, self.min_window_size(data));
+                    ctx.window().set_position(window_rect.origin());
+                    ctx.window().set_size(window_rect.size());
+                    return true;
+                }
+                let grip = window_resizing::grip_at(me.pos, ctx.size()).filter(|_| self.can_resize(data));
+                match grip {
+                    Some(grip) if grip.is_corner() => ctx.set_cursor(&Cursor::Crosshair),
+                    Some(grip) if grip.is_horizontal() => ctx.set_cursor(&Cursor::ResizeLeftRight),
+                    Some(_) => ctx.set_cursor(&Cursor::ResizeUpDown),
+                    None => ctx.clear_cursor(),
+                }
+                false
+            }
+            Event::MouseUp(_) if self.resize.is_some() => {
+                self.resize = None;
+                ctx.set_active(false);
+                self.record_window_rect(ctx.window(), data);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // The parts of the window that take input when the dock is hidden
+    fn input_area(&self, data: &AppState, window_size: Size) -> Region {
+        let mut input_area = Region::EMPTY;
+        input_area.add_rect(self.persistent_items.layout_rect());
+        if self.can_resize(data) {
+            for rect in window_resizing::grip_rects(window_size) {
+                input_area.add_rect(rect);
+            }
+        }
+        for placed in &self.docks {
+            let origin = placed.pane_dock.layout_rect().origin().to_vec2();
+            for rect in placed.pane_dock.widget().input_area().rects() {
+                input_area.add_rect(*rect + origin);
+            }
+        }
+        input_area
+    }
+}
+
+impl Widget<AppState> for DockWindow {
+    fn event(&mut self, ctx: &mut druid::EventCtx, event: &druid::Event, data: &mut AppState, env: &druid::Env) {
+        if self.handle_resizing(ctx, event, data) {
+            return;
+        }
+        let controls_had_pointer = self.dock_items.has_active() || self.persistent_items.has_active();
+        self.dock_items.event(ctx, event, data, env);
+        self.persistent_items.event(ctx, event, data, env);
+
+        if let Event::MouseDown(me) = event {
+            if let Some(index) = self.docks.iter().position(|placed| placed.pane_dock.layout_rect().contains(me.pos)) {
+                self.current_dock = index;
+            }
+        }
+        let is_dock_command = matches!(event, Event::Command(command) if DOCK_COMMANDS.iter().any(|selector| command.is(*selector)));
+        // The docks are laid out under the controls, so while a control has the pointer, like a slider
+        // being dragged, they don't get it, or they would start dragging the window too.
+        // That's from the press that a control takes, up to and including the release.
+        let is_pointer_event = matches!(event, Event::MouseDown(_) | Event::MouseUp(_) | Event::MouseMove(_) | Event::Wheel(_));
+        let controls_have_pointer = controls_had_pointer || self.dock_items.has_active() || self.persistent_items.has_active();
+        for (index, placed) in self.docks.iter_mut().enumerate() {
+            if (is_dock_command && index != self.current_dock) || (is_pointer_event && controls_have_pointer) {
+                continue;
+            }
+            // Only replace the dock if it changed, so that the other docks aren't updated
+            let mut dock = data.docks[placed.dock_index].clone();
+            placed.pane_dock.event(ctx, event, &mut dock, env);
+            if !dock.same(&data.docks[placed.dock_index]) {
+                data.docks.set(placed.dock_index, dock);
+            }
+        }
+
+        match event {
+            Event::Command(command) => self.handle_command(ctx, command, data),
+            Event::WindowConnected => self.restore_window(ctx.window(), data),
+            // After the window may have been moved or resized, or before it closes
+            Event::WindowSize(_) | Event::MouseUp(_) | Event::WindowCloseRequested => {
+                self.record_window_rect(ctx.window(), data);
+            }
+            _ => (),
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut druid::LifeCycleCtx, event: &druid::LifeCycle, data: &AppState, env: &druid::Env) {
+        self.dock_items.lifecycle(ctx, event, data, env);
+        self.persistent_items.lifecycle(ctx, event, data, env);
+        for placed in &mut self.docks {
+            placed.pane_dock.lifecycle(ctx, event, &data.docks[placed.dock_index], env);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut druid::UpdateCtx, old_data: &AppState, data: &AppState, env: &druid::Env) {
+        self.dock_items.update(ctx, data, env);
+        self.persistent_items.update(ctx, data, env);
+        for placed in &mut self.docks {
+            placed.pane_dock.update(ctx, &data.docks[placed.dock_index], env);
+        }
+
+        // The settings are shared, so every dock window follows them
+        if old_data.overlay_mode != data.overlay_mode {
+            self.apply_overlay_mode(ctx.window(), data);
+        }
+        if old_data.is_dock_shown() != data.is_dock_shown() {
+            if !data.overlay_mode {
+                ctx.window().show_titlebar(data.show_dock);
+            }
+            // The whole background changes, including where nothing is laid out
+            ctx.request_paint();
+            ctx.window().invalidate();
+            ctx.request_layout();
+        }
+        if old_data.appearance != data.appearance {
+            // The background color comes from it through the env
+            ctx.request_paint();
+        }
+        if old_data.always_on_top != data.always_on_top {
+            trace!(target: LOG_WINDOW, "Setting always on top to: {}", data.always_on_top);
+            ctx.window().set_always_on_top(data.always_on_top);
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut druid::LayoutCtx, bc: &druid::BoxConstraints, data: &AppState, env: &druid::Env) -> druid::Size {
+        let size = bc.max();
+        let inner_item_bc = BoxConstraints::new(Size::new(0.0, 0.0), size);
+
+        // Position to right
+        let persistent_items_size = self.persistent_items.layout(ctx, &inner_item_bc, data, env);
+        self.persistent_items.set_origin(ctx, Point::new(size.width - persistent_items_size.width, 0.0));
+        let persistent_items_rect = self.persistent_items.layout_rect();
+
+        if data.is_dock_shown() {
+            let dock_item_bc = BoxConstraints::new(inner_item_bc.min(),
+                Size::new(inner_item_bc.max().width - persistent_items_size.width, inner_item_bc.max().height));
+            let _dock_items_layout = self.dock_items.layout(ctx, &dock_item_bc, data, env);
+            self.dock_items.set_origin(ctx, Point::new(0.0, 0.0));
+        }
+
+        // Side by side, with the last one against the right side of the window
+        let total_flex: f64 = self.docks.iter().map(|placed| placed.flex).sum();
+        let mut next_x = 0.0;
+        for placed in &mut self.docks {
+            let width = size.width * placed.flex / total_flex;
+            let dock_rect = Rect::new(next_x, 0.0, next_x + width, size.height);
+            next_x += width;
+            // Maximized panes stay below the persistent items where they overlap
+            let covered_rect = persistent_items_rect.intersect(dock_rect);
+            let covered_rect = if covered_rect.area() > 0.0 {
+                covered_rect - dock_rect.origin().to_vec2()
+            } else {
+                Rect::ZERO
+            };
+            placed.pane_dock.widget_mut().set_persistent_items_rect(covered_rect);
+            placed.pane_dock.layout(ctx, &BoxConstraints::tight(dock_rect.size()), &data.docks[placed.dock_index], env);
+            placed.pane_dock.set_origin(ctx, dock_rect.origin());
+        }
+
+        // Only handed to the window when it changes, since that can be slow
+        let input_area = if data.is_dock_shown() { None } else { Some(self.input_area(data, size)) };
+        let input_rects = input_area.as_ref().map(|input_area| input_area.rects().to_vec());
+        if input_rects != self.input_rects {
+            trace!(target: LOG_LAYOUT, "Setting input region to {:?}", input_rects);
+            ctx.window().set_input_region(input_area);
+            self.input_rects = input_rects;
+        }
+
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut druid::PaintCtx, data: &AppState, env: &druid::Env) {
+        let paint_area = ctx.region().bounding_box();
+        let colors = DockColors::from_env(env);
+        dock_painting::paint_window_background(ctx.render_ctx, paint_area, data.is_dock_shown(), &colors);
+        if data.is_dock_shown() {
+            self.dock_items.paint(ctx, data, env);
+        }
+
+        self.persistent_items.paint(ctx, data, env);
+        if self.can_resize(data) && !data.is_dock_shown() {
+            let window_size = ctx.size();
+            dock_painting::paint_resize_border(ctx.render_ctx, window_size);
+        }
+
+        for placed in &mut self.docks {
+            placed.pane_dock.paint(ctx, &data.docks[placed.dock_index], env);
+        }
+    }
+}
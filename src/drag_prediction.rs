@@ -0,0 +1,85 @@
+use std::time::Duration;
+use crate::pane_layout::{self, PaneTarget};
+
+/// Settings for starting pane drags, and for predicting where a dragged pane will be dropped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DragSettings {
+    /// How far the pointer needs to move from where it was pressed before a drag starts.
+    /// This is in display points, like all druid positions, so it scales with the display's DPI.
+    /// Until then, releasing the pointer counts as a click.
+    pub drag_threshold: f64,
+    /// Holding the pointer down on a header for this long starts a drag without it moving.
+    pub hold_duration: Duration,
+    /// How much of each new movement goes into the smoothed velocity, from 0 to 1.
+    /// Lower values ignore more jitter, but are slower to follow a change in direction.
+    pub velocity_smoothing: f64,
+    /// The smoothed speed, in pixels per pointer move, at which the drop point leads the pane the most.
+    pub full_lead_speed: f64,
+    /// How far ahead of the dragged pane's center the drop point goes at full speed, as a fraction of its width.
+    /// Higher values swap panes sooner.
+    pub swap_lead: f64,
+    /// How far past a swap point the drop point needs to go before the panes swap, in pixels.
+    /// This keeps panes from flickering back and forth when the pointer stays near a swap point.
+    pub hysteresis: f64,
+}
+
+impl Default for DragSettings {
+    fn default() -> Self {
+        Self {
+            drag_threshold: 4.0,
+            hold_duration: Duration::from_millis(500),
+            velocity_smoothing: 0.3,
+            full_lead_speed: 4.0,
+            swap_lead: 0.75,
+            hysteresis: 12.0,
+        }
+    }
+}
+
+/// Follows the movement of a dragged pane to predict where the user wants to drop it.
+/// Positions are relative to the bound side, so a positive velocity is moving away from it.
+pub struct DragPredictor {
+    settings: DragSettings,
+    smoothed_velocity: f64,
+}
+
+impl DragPredictor {
+    pub fn new(settings: DragSettings) -> Self {
+        Self {
+            settings,
+            smoothed_velocity: 0.0,
+        }
+    }
+
+    /// Adds a pointer move, as the change to the dragged pane's x position.
+    pub fn add_movement(&mut self, x_pos_change: f64) {
+        self.smoothed_velocity += (x_pos_change - self.smoothed_velocity) * self.settings.velocity_smoothing;
+    }
+
+    pub fn velocity(&self) -> f64 {
+        self.smoothed_velocity
+    }
+
+    /// The position that the other panes are compared against to find where the pane would be dropped.
+    /// It leads the pane's center in the direction it's moving, further the faster it moves.
+    pub fn drop_x_pos(&self, actual_x_pos: f64, width: f64) -> f64 {
+        let lead_amount = (self.smoothed_velocity / self.settings.full_lead_speed).clamp(-1.0, 1.0);
+        actual_x_pos + width / 2.0 + lead_amount * self.settings.swap_lead * width
+    }
+
+    /// Finds the index the dragged pane needs to move to in the data.
+    /// It only moves once the drop position is past the swap point by the hysteresis.
+    pub fn new_index(&self, targets: &[PaneTarget], drop_x_pos: f64, dragged_index: usize) -> usize {
+        let new_index = pane_layout::get_new_index(targets, drop_x_pos, dragged_index);
+        if new_index == dragged_index {
+            return dragged_index;
+        }
+        // Pull the drop position back towards where the pane is now, and see if it still moves
+        let held_x_pos = if new_index > dragged_index {
+            drop_x_pos - self.settings.hysteresis
+        } else {
+            drop_x_pos + self.settings.hysteresis
+        };
+        pane_layout::get_new_index(targets, held_x_pos, dragged_index)
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::VecDeque;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Instant;
+use druid::{Event, KbKey, KeyEvent, Modifiers, MouseButton, MouseButtons, MouseEvent, Point, Size, Vec2};
+use druid::keyboard_types::KeyState;
+use serde::{Deserialize, Serialize};
+use crate::{DockData, PaneData};
+
+pub const DEFAULT_RECORDING_PATH: &str = "dock-recording.json";
+
+/// The mouse and keyboard events that reached the dock, with the animation frames between them.
+/// The dock state from the start is included, so that replaying it ends at the same layout.
+#[derive(Serialize, Deserialize)]
+pub struct Recording {
+    pub dock_size: (f64, f64),
+    pub initial_panes: Vec<PaneData>,
+    pub initial_maximized_pane: Option<i32>,
+    pub events: Vec<RecordedEvent>,
+    // For checking that a replay ended the same way
+    pub final_panes: Vec<PaneData>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    // Time since the recording started
+    pub time_micros: u64,
+    pub kind: RecordedEventKind,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum RecordedEventKind {
+    MouseDown(RecordedMouse),
+    MouseUp(RecordedMouse),
+    MouseMove(RecordedMouse),
+    Wheel(RecordedMouse),
+    KeyDown(RecordedKey),
+    KeyUp(RecordedKey),
+    AnimFrame { interval: u64 },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RecordedMouse {
+    // Relative to the dock. Older recordings were made when the dock was the whole window.
+    #[serde(alias = "window_pos")]
+    pub pos: (f64, f64),
+    pub buttons: Vec<u8>,
+    pub button: u8,
+    pub count: u8,
+    pub mods: RecordedModifiers,
+    pub wheel_delta: (f64, f64),
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RecordedKey {
+    pub key: String,
+    pub mods: RecordedModifiers,
+    pub repeat: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RecordedModifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+const MOUSE_BUTTONS: [MouseButton; 5] = [
+    MouseButton::Left, MouseButton::Right, MouseButton::Middle, MouseButton::X1, MouseButton::X2,
+];
+
+fn mouse_button_to_u8(button: MouseButton) -> u8 {
+    match button {
+        MouseButton::Left => 1,
+        MouseButton::Right => 2,
+        MouseButton::Middle => 3,
+        MouseButton::X1 => 4,
+        MouseButton::X2 => 5,
+        _ => 0,
+    }
+}
+
+fn mouse_button_from_u8(button: u8) -> MouseButton {
+    match button {
+        1 => MouseButton::Left,
+        2 => MouseButton::Right,
+        3 => MouseButton::Middle,
+        4 => MouseButton::X1,
+        5 => MouseButton::X2,
+        _ => MouseButton::None,
+    }
+}
+
+impl RecordedModifiers {
+    fn from_modifiers(mods: &Modifiers) -> Self {
+        Self { shift: mods.shift(), ctrl: mods.ctrl(), alt: mods.alt(), meta: mods.meta() }
+    }
+
+    fn to_modifiers(&self) -> Modifiers {
+        let mut mods = Modifiers::empty();
+        mods.set(Modifiers::SHIFT, self.shift);
+        mods.set(Modifiers::CONTROL, self.ctrl);
+        mods.set(Modifiers::ALT, self.alt);
+        mods.set(Modifiers::META, self.meta);
+        mods
+    }
+}
+
+impl RecordedMouse {
+    fn from_mouse_event(mouse_event: &MouseEvent) -> Self {
+        Self {
+            pos: (mouse_event.pos.x, mouse_event.pos.y),
+            buttons: MOUSE_BUTTONS.iter()
+                .filter(|button| mouse_event.buttons.contains(**button))
+                .map(|button| mouse_button_to_u8(*button))
+                .collect(),
+            button: mouse_button_to_u8(mouse_event.button),
+            count: mouse_event.count,
+            mods: RecordedModifiers::from_modifiers(&mouse_event.mods),
+            wheel_delta: (mouse_event.wheel_delta.x, mouse_event.wheel_delta.y),
+        }
+    }
+
+    fn to_mouse_event(&self) -> MouseEvent {
+        let pos = Point::new(self.pos.0, self.pos.1);
+        let mut buttons = MouseButtons::new();
+        for button in &self.buttons {
+            buttons.insert(mouse_button_from_u8(*button));
+        }
+        MouseEvent {
+            // Replayed events go straight to the dock, which only uses the position within it
+            pos,
+            window_pos: pos,
+            buttons,
+            mods: self.mods.to_modifiers(),
+            count: self.count,
+            focus: false,
+            button: mouse_button_from_u8(self.button),
+            wheel_delta: Vec2::new(self.wheel_delta.0, self.wheel_delta.1),
+        }
+    }
+}
+
+impl RecordedKey {
+    fn from_key_event(key_event: &KeyEvent) -> Self {
+        Self {
+            key: key_event.key.to_string(),
+            mods: RecordedModifiers::from_modifiers(&key_event.mods),
+            repeat: key_event.repeat,
+        }
+    }
+
+    fn to_key_event(&self, state: KeyState) -> KeyEvent {
+        let mut key_event = KeyEvent::default();
+        key_event.state = state;
+        key_event.key = KbKey::from_str(&self.key).unwrap_or(KbKey::Unidentified);
+        key_event.mods = self.mods.to_modifiers();
+        key_event.repeat = self.repeat;
+        key_event
+    }
+}
+
+impl RecordedEventKind {
+    /// Returns None for events that aren't recorded
+    pub fn from_event(event: &Event) -> Option<Self> {
+        match event {
+            Event::MouseDown(mouse_event) => Some(Self::MouseDown(RecordedMouse::from_mouse_event(mouse_event))),
+            Event::MouseUp(mouse_event) => Some(Self::MouseUp(RecordedMouse::from_mouse_event(mouse_event))),
+            Event::MouseMove(mouse_event) => Some(Self::MouseMove(RecordedMouse::from_mouse_event(mouse_event))),
+            Event::Wheel(mouse_event) => Some(Self::Wheel(RecordedMouse::from_mouse_event(mouse_event))),
+            Event::KeyDown(key_event) => Some(Self::KeyDown(RecordedKey::from_key_event(key_event))),
+            Event::KeyUp(key_event) => Some(Self::KeyUp(RecordedKey::from_key_event(key_event))),
+            Event::AnimFrame(interval) => Some(Self::AnimFrame { interval: *interval }),
+            _ => None,
+        }
+    }
+
+    pub fn to_event(&self) -> Event {
+        match self {
+            Self::MouseDown(mouse) => Event::MouseDown(mouse.to_mouse_event()),
+            Self::MouseUp(mouse) => Event::MouseUp(mouse.to_mouse_event()),
+            Self::MouseMove(mouse) => Event::MouseMove(mouse.to_mouse_event()),
+            Self::Wheel(mouse) => Event::Wheel(mouse.to_mouse_event()),
+            Self::KeyDown(key) => Event::KeyDown(key.to_key_event(KeyState::Down)),
+            Self::KeyUp(key) => Event::KeyUp(key.to_key_event(KeyState::Up)),
+            Self::AnimFrame { interval } => Event::AnimFrame(*interval),
+        }
+    }
+
+    pub fn is_user_input(&self) -> bool {
+        !matches!(self, Self::AnimFrame { .. })
+    }
+}
+
+pub struct EventRecorder {
+    start: Instant,
+    recording: Recording,
+}
+
+impl EventRecorder {
+    pub fn new(dock_size: Size, dock: &DockData) -> Self {
+        Self {
+            start: Instant::now(),
+            recording: Recording {
+                dock_size: (dock_size.width, dock_size.height),
+                initial_panes: dock.panes.iter().cloned().collect(),
+                initial_maximized_pane: dock.maximized_pane,
+                events: vec![],
+                final_panes: vec![],
+            },
+        }
+    }
+
+    pub fn record(&mut self, event: &Event) {
+        if let Some(kind) = RecordedEventKind::from_event(event) {
+            let time_micros = self.start.elapsed().as_micros() as u64;
+            self.recording.events.push(RecordedEvent { time_micros, kind });
+        }
+    }
+
+    pub fn finish(mut self, final_panes: &druid::im::Vector<PaneData>) -> Recording {
+        self.recording.final_panes = final_panes.iter().cloned().collect();
+        self.recording
+    }
+}
+
+impl Recording {
+    /// Puts the dock data back to how it was when recording started
+    pub fn apply_initial_state(&self, dock: &mut DockData) {
+        // The panes were made before the recording, so their IDs are still free
+        dock.panes = self.initial_panes.iter().cloned().collect();
+        dock.maximized_pane = self.initial_maximized_pane;
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+/// Feeds a recording back one animation frame at a time, so that the animations
+/// get the same frame intervals as when it was recorded.
+pub struct EventReplay {
+    events: VecDeque<RecordedEvent>,
+    pub final_panes: Vec<PaneData>,
+}
+
+impl EventReplay {
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            events: recording.events.into(),
+            final_panes: recording.final_panes,
+        }
+    }
+
+    /// Returns the events up to and including the next animation frame.
+    pub fn next_frame(&mut self) -> Vec<Event> {
+        let mut frame_events = vec![];
+        while let Some(recorded_event) = self.events.pop_front() {
+            frame_events.push(recorded_event.kind.to_event());
+            if !recorded_event.kind.is_user_input() {
+                break;
+            }
+        }
+        frame_events
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+}
@@ -0,0 +1,165 @@
+use std::path::PathBuf;
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+use druid::{DelegateCtx, ExtEventSink, Selector, SingleUse, Target};
+use serde::{Deserialize, Serialize};
+use crate::AppState;
+use crate::cli::Cli;
+use crate::pane_dock_widget::FOCUS_PANE;
+
+/// How long a connection waits for the app to answer a request.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A request from another process. Each is one line of JSON, like `{"command": "open", "title": "Notes"}`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum IpcRequest {
+    /// Adds a pane to the main dock
+    Open {
+        #[serde(default)]
+        title: Option<String>,
+    },
+    Close { pane_id: i32 },
+    /// Restores the pane if it's minimized, focuses it, and brings its window to the front
+    Focus { pane_id: i32 },
+    List,
+    SetTitle { pane_id: i32, title: Option<String> },
+    SetBadge { pane_id: i32, badge: Option<u32> },
+    /// The command line of another launch, which passes it on instead of opening a second dock.
+    /// The first argument is the program, like in `std::env::args`.
+    /// Relative paths in them are taken from `cwd`, the working directory of that launch.
+    Launch {
+        args: Vec<String>,
+        #[serde(default)]
+        cwd: Option<PathBuf>,
+    },
+}
+
+/// The answer to a request, as one line of JSON.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IpcResponse {
+    pub ok: bool,
+    /// The pane that was opened, or the last one for a launch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pane_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub panes: Option<Vec<PaneInfo>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PaneInfo {
+    pub id: i32,
+    pub dock: usize,
+    pub title: Option<String>,
+    pub badge: Option<u32>,
+    pub is_minimized: bool,
+}
+
+impl IpcResponse {
+    fn ok() -> Self {
+        Self { ok: true, ..Self::default() }
+    }
+
+    pub fn error(error: impl Into<String>) -> Self {
+        Self { ok: false, error: Some(error.into()), ..Self::default() }
+    }
+}
+
+/// Makes the change to the state that the request asks for.
+/// Focusing also needs the dock widgets, which `handle_request` takes care of.
+pub fn apply_request(state: &mut AppState, request: &IpcRequest) -> IpcResponse {
+    let pane_id = match request {
+        IpcRequest::Open { title } => {
+            let pane_id = state.add_titled_pane(0, title.clone());
+            return IpcResponse { pane_id: Some(pane_id), ..IpcResponse::ok() };
+        }
+        IpcRequest::List => {
+            let panes = state.docks.iter().enumerate()
+                .flat_map(|(dock_index, dock)| dock.panes.iter().map(move |pane| PaneInfo {
+                    id: pane.id,
+                    dock: dock_index,
+                    title: pane.title.clone(),
+                    badge: pane.badge,
+                    is_minimized: pane.is_minimized,
+                }))
+                .collect();
+            return IpcResponse { panes: Some(panes), ..IpcResponse::ok() };
+        }
+        IpcRequest::Launch { args, cwd } => {
+            let mut cli = match Cli::try_parse_from(args) {
+                Ok(cli) => cli,
+                Err(err) => return IpcResponse::error(err.to_string()),
+            };
+            if let Some(cwd) = cwd {
+                cli.resolve_paths(cwd);
+            }
+            return match cli.apply_to_running(state) {
+                Ok(opened_panes) => IpcResponse { pane_id: opened_panes.last().copied(), ..IpcResponse::ok() },
+                Err(err) => IpcResponse::error(err.to_string()),
+            };
+        }
+        IpcRequest::Close { pane_id } | IpcRequest::Focus { pane_id }
+            | IpcRequest::SetTitle { pane_id, .. } | IpcRequest::SetBadge { pane_id, .. } => *pane_id,
+    };
+    let (dock_index, pane_index) = match state.find_pane(pane_id) {
+        Some(found) => found,
+        None => return IpcResponse::error(format!("There is no pane {}", pane_id)),
+    };
+    let dock = &mut state.docks[dock_index];
+    match request {
+        IpcRequest::Close { .. } => dock.close_pane(pane_index),
+        IpcRequest::Focus { .. } => {
+            if dock.panes[pane_index].is_minimized {
+                dock.toggle_minimized(pane_index);
+            }
+        }
+        IpcRequest::SetTitle { title, .. } => dock.panes[pane_index].title = title.clone(),
+        IpcRequest::SetBadge { badge, .. } => dock.panes[pane_index].badge = *badge,
+        IpcRequest::Open { .. } | IpcRequest::List | IpcRequest::Launch { .. } => unreachable!("Handled above"),
+    }
+    IpcResponse::ok()
+}
+
+/// A request on its way to the app, which answers it once it has been applied.
+pub struct PendingRequest {
+    pub request: IpcRequest,
+    reply: mpsc::Sender<IpcResponse>,
+}
+
+/// Sent to the app for each request that comes in.
+pub const IPC_REQUEST: Selector<SingleUse<PendingRequest>> = Selector::new("polysoft.druid-demo.ipc-request");
+
+/// Applies a request that came in through `IPC_REQUEST`, from the app delegate, and answers it.
+pub fn handle_request(ctx: &mut DelegateCtx, pending: PendingRequest, state: &mut AppState) {
+    let response = apply_request(state, &pending.request);
+    let focused_pane = match &pending.request {
+        IpcRequest::Focus { pane_id } if response.ok => Some(*pane_id),
+        // Launching again brings the dock to the front, even if it didn't open any panes
+        IpcRequest::Launch { .. } if response.ok => response.pane_id
+            .or_else(|| state.docks.front().and_then(|dock| dock.panes.last()).map(|pane| pane.id)),
+        _ => None,
+    };
+    if let Some(pane_id) = focused_pane {
+        ctx.submit_command(FOCUS_PANE.with(pane_id).to(Target::Global));
+    }
+    // The connection may have given up waiting
+    let _ = pending.reply.send(response);
+}
+
+/// A handler for the server that passes the requests to the running app, and waits for its answer.
+pub fn forward_to_app(sink: ExtEventSink) -> impl Fn(IpcRequest) -> IpcResponse + Send + Sync + 'static {
+    let sink = Mutex::new(sink);
+    move |request| {
+        let (reply, response) = mpsc::channel();
+        let pending = PendingRequest { request, reply };
+        let submitted = sink.lock().expect("The sink lock is never poisoned")
+            .submit_command(IPC_REQUEST, SingleUse::new(pending), Target::Auto);
+        if submitted.is_err() {
+            return IpcResponse::error("The dock is closing");
+        }
+        response.recv_timeout(REPLY_TIMEOUT)
+            .unwrap_or_else(|_| IpcResponse::error("The dock didn't answer"))
+    }
+}
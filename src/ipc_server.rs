@@ -0,0 +1,122 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use tracing::{trace, warn};
+use crate::LOG_IPC;
+use crate::ipc::{IpcRequest, IpcResponse};
+
+/// The socket for `--single-instance`, so that launching it again finds the one running.
+pub fn default_socket_path() -> PathBuf {
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from).unwrap_or_else(std::env::temp_dir);
+    runtime_dir.join("druid-pane-demo.sock")
+}
+
+/// Sends one request to the dock listening on the socket, and waits for the answer.
+/// Fails if no dock is listening there.
+pub fn send_request(path: impl AsRef<Path>, request: &IpcRequest) -> io::Result<IpcResponse> {
+    let mut stream = UnixStream::connect(path)?;
+    let mut json = serde_json::to_string(request)?;
+    json.push('\n');
+    stream.write_all(json.as_bytes())?;
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response)?;
+    Ok(serde_json::from_str(&response)?)
+}
+
+/// Listens on a Unix domain socket, and answers each line of JSON with a line of JSON from the handler.
+/// The socket file is removed when it's dropped, unless something else has taken its place.
+pub struct IpcServer {
+    path: PathBuf,
+    // The device and inode of the socket file, to tell it apart from one made by another dock since
+    socket_file: (u64, u64),
+}
+
+/// Removes a socket file left behind by a dock that has quit, so that it can be listened on again.
+/// Fails if another dock is still listening there, or if there is something other than a socket at the path.
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists,
+            format!("{} is there already, and isn't a socket", path.display())));
+    }
+    match UnixStream::connect(path) {
+        Ok(_) => Err(io::Error::new(io::ErrorKind::AddrInUse,
+            format!("Another dock is listening on {}", path.display()))),
+        Err(_) => {
+            trace!(target: LOG_IPC, "Removing the socket left behind at {}", path.display());
+            std::fs::remove_file(path)
+        }
+    }
+}
+
+impl IpcServer {
+    /// Starts listening in the background. A socket left behind by a dock that has quit is replaced.
+    pub fn start(path: impl AsRef<Path>, handler: impl Fn(IpcRequest) -> IpcResponse + Send + Sync + 'static) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        remove_stale_socket(&path)?;
+        let listener = UnixListener::bind(&path)?;
+        let metadata = std::fs::symlink_metadata(&path)?;
+        let socket_file = (metadata.dev(), metadata.ino());
+        trace!(target: LOG_IPC, "Listening on {}", path.display());
+        let handler = Arc::new(handler);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let handler = handler.clone();
+                        thread::spawn(move || {
+                            if let Err(err) = serve_connection(stream, &*handler) {
+                                warn!(target: LOG_IPC, "Connection failed: {}", err);
+                            }
+                        });
+                    }
+                    Err(err) => warn!(target: LOG_IPC, "Could not accept a connection: {}", err),
+                }
+            }
+        });
+        Ok(Self { path, socket_file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        let is_own_socket = std::fs::symlink_metadata(&self.path)
+            .map(|metadata| (metadata.dev(), metadata.ino()) == self.socket_file)
+            .unwrap_or(false);
+        if is_own_socket {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn serve_connection(stream: UnixStream, handler: &dyn Fn(IpcRequest) -> IpcResponse) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str(&line) {
+            Ok(request) => {
+                trace!(target: LOG_IPC, "Got {:?}", request);
+                handler(request)
+            }
+            Err(err) => IpcResponse::error(format!("Could not read the request: {}", err)),
+        };
+        let mut json = serde_json::to_string(&response)?;
+        json.push('\n');
+        writer.write_all(json.as_bytes())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use druid::{Color, Data, Lens, Rect};
+use druid::im;
+use serde::{Deserialize, Serialize};
+
+pub mod cli;
+pub mod dock_history;
+pub mod dock_model;
+pub mod dock_painting;
+pub mod dock_window;
+pub mod drag_prediction;
+pub mod event_recording;
+pub mod ipc;
+#[cfg(unix)]
+pub mod ipc_server;
+pub mod pane_dock_widget;
+pub mod pane_feed;
+pub mod pane_header_widget;
+pub mod pane_layout;
+pub mod pane_widget;
+pub mod theme;
+pub mod user_settings;
+pub mod window_resizing;
+pub mod window_snapping;
+
+pub const DEFAULT_PANE_HEIGHT: f64 = 480.0;
+pub const DEFAULT_PANE_WIDTH: f64 = 300.0;
+pub const PANE_SPACING: f64 = 8.0;
+pub const HEADER_HEIGHT: f64 = 25.0;
+pub const DOCK_BACKGROUND_COLOR: Color = Color::rgba8(0, 0, 0, 200);
+pub const PANE_BACKGROUND_COLOR: Color = Color::rgb8(70, 70, 70);
+pub const PANE_HEADER_COLOR: Color = Color::rgb8(40, 40, 40);
+pub const PANE_SCROLLBAR_COLOR: Color = Color::rgba8(200, 200, 200, 160);
+pub const PANE_HEADER_BUTTON_COLOR: Color = Color::rgba8(255, 255, 255, 20);
+
+pub const DOCK_BACKGROUND_COLOR_KEY: druid::env::Key<druid::Color> = druid::env::Key::new("polysoft.druid-demo.dock-background-color");
+pub const PANE_BACKGROUND_COLOR_KEY: druid::env::Key<druid::Color> = druid::env::Key::new("polysoft.druid-demo.pane-background-color");
+pub const PANE_HEADER_COLOR_KEY: druid::env::Key<druid::Color> = druid::env::Key::new("polysoft.druid-demo.pane-header-color");
+pub const PANE_HEADER_BUTTON_COLOR_KEY: druid::env::Key<druid::Color> = druid::env::Key::new("polysoft.druid-demo.pane-header-button-color");
+pub const IDLE_PANE_OPACITY_KEY: druid::env::Key<f64> = druid::env::Key::new("polysoft.druid-demo.idle-pane-opacity");
+pub const DIM_UNFOCUSED_PANES_KEY: druid::env::Key<bool> = druid::env::Key::new("polysoft.druid-demo.dim-unfocused-panes");
+
+// Log targets, so that the output for each part of the dock can be enabled on its own.
+// For example: RUST_LOG=dock::drag=trace
+pub(crate) const LOG_DIFFING: &str = "dock::diffing";
+pub(crate) const LOG_LAYOUT: &str = "dock::layout";
+pub(crate) const LOG_DRAG: &str = "dock::drag";
+pub(crate) const LOG_ANIMATION: &str = "dock::animation";
+pub(crate) const LOG_WINDOW: &str = "dock::window";
+pub(crate) const LOG_REPLAY: &str = "dock::replay";
+pub(crate) const LOG_IPC: &str = "dock::ipc";
+
+
+#[derive(Clone, Debug, Data, Lens, Serialize, Deserialize)]
+pub struct PaneData {
+    pub id: i32,
+    pub width: f64,
+    pub height: f64, // Height of the contents, not including the header
+    pub is_minimized: bool,
+    /// Shown in the header instead of the pane ID
+    #[serde(default)]
+    pub title: Option<String>,
+    /// A count shown next to the title, like unread messages
+    #[serde(default)]
+    pub badge: Option<u32>,
+    /// What the pane shows, which can be fed in from other threads
+    #[serde(default)]
+    pub text: String,
+}
+
+impl PaneData {
+    pub fn new(id: i32) -> Self {
+        Self {
+            id,
+            width: DEFAULT_PANE_WIDTH,
+            height: DEFAULT_PANE_HEIGHT,
+            is_minimized: false,
+            title: None,
+            badge: None,
+            text: String::new(),
+        }
+    }
+
+    pub fn header_text(&self) -> String {
+        let title = match &self.title {
+            Some(title) => title.clone(),
+            None => format!("Pane {} header", self.id),
+        };
+        match self.badge {
+            Some(badge) => format!("{} ({})", title, badge),
+            None => title,
+        }
+    }
+
+    pub fn content_text(&self) -> String {
+        if self.text.is_empty() {
+            format!("Pane {} content", self.id)
+        } else {
+            self.text.clone()
+        }
+    }
+
+    /// Takes what is shown in the pane from another version of it, leaving its place in the layout.
+    /// That comes from outside the dock, so undo doesn't change it.
+    pub fn copy_contents_from(&mut self, other: &PaneData) {
+        self.title = other.title.clone();
+        self.badge = other.badge;
+        self.text = other.text.clone();
+    }
+}
+
+/// How far the panes of a dock slide down when it auto-hides.
+#[derive(Clone, Copy, Debug, Data, PartialEq, Eq)]
+pub enum AutoHide {
+    Off,
+    /// Down to a thin strip along the bottom
+    ToStrip,
+    /// Down to their headers
+    ToHeaders,
+}
+
+impl AutoHide {
+    /// The next mode, for cycling through them with a button.
+    pub fn next(self) -> Self {
+        match self {
+            AutoHide::Off => AutoHide::ToStrip,
+            AutoHide::ToStrip => AutoHide::ToHeaders,
+            AutoHide::ToHeaders => AutoHide::Off,
+        }
+    }
+}
+
+/// The panes in one dock.
+#[derive(Clone, Data, Lens)]
+pub struct DockData {
+    /// In dock order, starting from the bound side.
+    pub panes: im::Vector<PaneData>,
+    pub maximized_pane: Option<i32>,
+    pub auto_hide: AutoHide,
+}
+
+impl DockData {
+    pub fn new(panes: im::Vector<PaneData>) -> Self {
+        Self {
+            panes,
+            maximized_pane: None,
+            auto_hide: AutoHide::Off,
+        }
+    }
+
+    pub fn add_pane(&mut self, pane: PaneData) {
+        self.panes.push_back(pane);
+        // So that the new pane can be seen
+        self.maximized_pane = None;
+    }
+
+    pub fn close_pane(&mut self, index: usize) {
+        let removed_pane = self.panes.remove(index);
+        if self.maximized_pane == Some(removed_pane.id) {
+            self.maximized_pane = None;
+        }
+    }
+
+    pub fn toggle_maximized(&mut self, index: usize) {
+        let pane_id = self.panes[index].id;
+        self.maximized_pane = if self.maximized_pane == Some(pane_id) {
+            None
+        } else {
+            Some(pane_id)
+        };
+    }
+
+    pub fn toggle_minimized(&mut self, index: usize) {
+        // Minimizing the maximized pane puts the others back
+        self.maximized_pane = None;
+        let pane_data = &mut self.panes[index];
+        pane_data.is_minimized = !pane_data.is_minimized;
+    }
+}
+
+/// How see-through the dock is. These can be changed while it runs, and are saved with the user settings.
+#[derive(Clone, Copy, Debug, Data, Lens, PartialEq, Serialize, Deserialize)]
+pub struct Appearance {
+    /// From 0 to 1, for the background behind the panes when the dock is shown
+    pub background_opacity: f64,
+    /// From 0 to 1, for the panes the pointer isn't over
+    pub idle_pane_opacity: f64,
+    /// Darkens every pane but the one last clicked
+    pub dim_unfocused_panes: bool,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self {
+            background_opacity: DOCK_BACKGROUND_COLOR.as_rgba().3,
+            idle_pane_opacity: 1.0,
+            dim_unfocused_panes: false,
+        }
+    }
+}
+
+#[derive(Clone, Data, Lens)]
+pub struct AppState {
+    pub show_dock: bool,
+    pub always_on_top: bool,
+    /// The dock windows fill the work area of their monitor, and only the panes and persistent controls take input.
+    pub overlay_mode: bool,
+    pub appearance: Appearance,
+    pub theme: theme::Theme,
+    /// Where the main window is on the screen, outside of overlay mode, so that it can be saved.
+    /// When the window opens, it's put here, kept on the screen.
+    pub main_window_rect: Option<Rect>,
+    /// One for each dock window, which refers to it by its index.
+    pub docks: im::Vector<DockData>,
+    // Pane IDs are unique across the docks, so that panes can move between them
+    pub next_pane_id: i32,
+}
+
+impl AppState {
+    /// Starts with one dock with the panes, shown, and with the default settings.
+    pub fn new(panes: im::Vector<PaneData>) -> Self {
+        let next_pane_id = panes.iter().map(|pane| pane.id + 1).max().unwrap_or(0);
+        Self {
+            show_dock: true,
+            always_on_top: false,
+            overlay_mode: false,
+            appearance: Appearance::default(),
+            theme: theme::Theme::default(),
+            main_window_rect: None,
+            docks: im::vector![DockData::new(panes)],
+            next_pane_id,
+        }
+    }
+
+    /// True if the dock's background and controls are shown, which never happens in overlay mode.
+    pub fn is_dock_shown(&self) -> bool {
+        self.show_dock && !self.overlay_mode
+    }
+
+    /// Adds a pane to the dock, and returns its ID.
+    pub fn add_pane(&mut self, dock_index: usize) -> i32 {
+        self.add_titled_pane(dock_index, None)
+    }
+
+    pub fn add_titled_pane(&mut self, dock_index: usize, title: Option<String>) -> i32 {
+        let pane_id = self.next_pane_id;
+        let mut pane = PaneData::new(pane_id);
+        pane.title = title;
+        self.docks[dock_index].add_pane(pane);
+        self.next_pane_id += 1;
+        pane_id
+    }
+
+    /// The index of the dock with the pane, and the pane's index in it.
+    pub fn find_pane(&self, pane_id: i32) -> Option<(usize, usize)> {
+        self.docks.iter().enumerate().find_map(|(dock_index, dock)| {
+            let pane_index = dock.panes.iter().position(|pane| pane.id == pane_id)?;
+            Some((dock_index, pane_index))
+        })
+    }
+
+    /// Adds an empty dock, and returns its index.
+    pub fn add_dock(&mut self) -> usize {
+        self.docks.push_back(DockData::new(im::Vector::new()));
+        self.docks.len() - 1
+    }
+}
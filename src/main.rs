@@ -1,72 +1,155 @@
-use druid::{AppLauncher, Color, Data, Lens, WindowDesc};
-use druid::widget::EnvScope;
+use druid::{AppLauncher, AppDelegate, Command, DelegateCtx, Env, Event, Handled, HotKey, SysMods, Target, WindowId};
 use druid::im;
+use clap::Parser;
+use tracing::warn;
+use druid_pane_demo::{AppState, PaneData, dock_window, pane_dock_widget};
+use druid_pane_demo::cli::{self, Cli};
+use druid_pane_demo::{ipc, pane_feed};
+use std::path::PathBuf;
+#[cfg(unix)]
+use druid_pane_demo::ipc_server::{self, IpcServer};
+use druid_pane_demo::user_settings::{self, UserSettings};
 
-mod pane_dock_widget;
-mod pane_header_widget;
-mod pane_widget;
-
-const DEFAULT_PANE_HEIGHT: f64 = 480.0;
-const DEFAULT_PANE_WIDTH: f64 = 300.0;
-const PANE_SPACING: f64 = 8.0;
-const HEADER_HEIGHT: f64 = 25.0;
-const DOCK_BACKGROUND_COLOR: Color = Color::rgba8(0, 0, 0, 200);
-const PANE_BACKGROUND_COLOR: Color = Color::rgb8(70, 70, 70);
-const PANE_HEADER_COLOR: Color = Color::rgb8(40, 40, 40);
+struct Delegate {
+    // Where the settings are saved, if there is anywhere to save them
+    settings_path: Option<PathBuf>,
+}
 
-pub const DOCK_BACKGROUND_COLOR_KEY: druid::env::Key<druid::Color> = druid::env::Key::new("polysoft.druid-demo.dock-background-color");
-pub const PANE_BACKGROUND_COLOR_KEY: druid::env::Key<druid::Color> = druid::env::Key::new("polysoft.druid-demo.pane-background-color");
-pub const PANE_HEADER_COLOR_KEY: druid::env::Key<druid::Color> = druid::env::Key::new("polysoft.druid-demo.pane-header-color");
+impl AppDelegate<AppState> for Delegate {
+    fn event(&mut self, ctx: &mut DelegateCtx, window_id: WindowId, event: Event, _data: &mut AppState, _env: &Env) -> Option<Event> {
+        if let Event::KeyDown(key_event) = &event {
+            let command = if HotKey::new(SysMods::Cmd, "z").matches(key_event) {
+                Some(pane_dock_widget::UNDO)
+            } else if HotKey::new(SysMods::CmdShift, "Z").matches(key_event) {
+                Some(pane_dock_widget::REDO)
+            } else if HotKey::new(SysMods::CmdShift, "T").matches(key_event) {
+                Some(pane_dock_widget::REOPEN_CLOSED_PANE)
+            } else if HotKey::new(SysMods::CmdShift, "D").matches(key_event) {
+                Some(pane_dock_widget::TOGGLE_DEBUG_OVERLAY)
+            } else if HotKey::new(SysMods::CmdShift, "R").matches(key_event) {
+                Some(pane_dock_widget::TOGGLE_RECORDING)
+            } else if HotKey::new(SysMods::CmdShift, "P").matches(key_event) {
+                Some(pane_dock_widget::REPLAY_RECORDING)
+            } else {
+                None
+            };
+            if let Some(command) = command {
+                ctx.submit_command(command.to(window_id));
+                return None;
+            }
+        }
+        Some(event)
+    }
 
+    fn command(&mut self, ctx: &mut DelegateCtx, _target: Target, command: &Command, data: &mut AppState, _env: &Env) -> Handled {
+        if let Some(pending) = command.get(ipc::IPC_REQUEST).and_then(|pending| pending.take()) {
+            ipc::handle_request(ctx, pending, data);
+            return Handled::Yes;
+        }
+        if let Some(feed_update) = command.get(pane_feed::PANE_FEED_UPDATE) {
+            // The pane may have been closed since the update was sent
+            pane_feed::apply_update(data, feed_update);
+            return Handled::Yes;
+        }
+        Handled::No
+    }
 
-#[derive(Clone, Data, Lens)]
-struct PaneData {
-    id: i32,
+    fn window_removed(&mut self, _id: WindowId, data: &mut AppState, _env: &Env, _ctx: &mut DelegateCtx) {
+        // Saved whenever a window closes, since the app quits after the last one
+        let settings_path = match &self.settings_path {
+            Some(settings_path) => settings_path,
+            None => return,
+        };
+        if let Err(err) = UserSettings::from_state(data).save(settings_path) {
+            warn!("Could not save the settings to {}: {}", settings_path.display(), err);
+        }
+    }
 }
 
-#[derive(Clone, Data, Lens)]
-struct AppState {
-    show_dock: bool,
-    always_on_top: bool,
-    panes: im::Vector<PaneData>,
-    next_pane_id: i32,
+// The server is only started when it's asked for
+#[cfg(unix)]
+fn ipc_socket_path(cli: &Cli) -> Option<PathBuf> {
+    match &cli.ipc_socket {
+        Some(path) => Some(path.clone()),
+        None if cli.single_instance => Some(ipc_server::default_socket_path()),
+        None => None,
+    }
 }
 
-impl AppState {
-    pub fn add_pane(&mut self) {
-        self.panes.push_back(PaneData { id: self.next_pane_id });
-        self.next_pane_id += 1;
+// Returns true if a dock is already running, and took the options instead
+fn pass_on_to_running_dock(cli: &Cli) -> bool {
+    if cli.headless_dump_layout {
+        return false;
     }
+    #[cfg(unix)]
+    if let Some(path) = ipc_socket_path(cli) {
+        // So that relative paths are found from here, rather than from where the running dock was started
+        let cwd = std::env::current_dir().ok();
+        let request = ipc::IpcRequest::Launch { args: std::env::args().collect(), cwd };
+        // Nothing is listening if this is the first launch
+        if let Ok(response) = ipc_server::send_request(&path, &request) {
+            for option in cli.options_ignored_when_running() {
+                eprintln!("Ignoring {}, since a dock is already running", option);
+            }
+            if let Some(err) = response.error {
+                eprintln!("{}", err);
+            }
+            return true;
+        }
+    }
+    false
 }
 
 fn main() {
-    let root = EnvScope::new(
-        |env: &mut druid::env::Env, _data: &AppState| {
-            env.set(DOCK_BACKGROUND_COLOR_KEY, DOCK_BACKGROUND_COLOR);
-            env.set(PANE_BACKGROUND_COLOR_KEY, PANE_BACKGROUND_COLOR);
-            env.set(PANE_HEADER_COLOR_KEY, PANE_HEADER_COLOR);
-        },
-        pane_dock_widget::PaneDockWidget::new()
-    );
-    let mut main_window = WindowDesc::new(root)
-        .title("Pane Demo Main Win")
-        .window_size((1024.0, DEFAULT_PANE_HEIGHT + HEADER_HEIGHT + 100.0))
-        .transparent(true);
-    if cfg!(target_os = "macos") {
-        main_window = main_window.show_titlebar(false);
+    let cli = Cli::parse();
+    if pass_on_to_running_dock(&cli) {
+        return;
+    }
+    let mut state = AppState::new(im::vector![PaneData::new(0)]);
+    // There are none on the first run. The dumped layout only depends on the options, for scripts.
+    let settings_path = user_settings::default_settings_path();
+    // Printed, since nothing is logged until the app launches
+    if settings_path.is_none() && !cli.headless_dump_layout {
+        eprintln!("There is no config directory to keep the settings in, so they won't be saved");
+    }
+    if !cli.headless_dump_layout {
+        if let Some(settings) = settings_path.as_ref().and_then(|path| UserSettings::load(path).ok()) {
+            settings.apply(&mut state);
+        }
+    }
+    if let Err(err) = cli.apply(&mut state) {
+        eprintln!("{}", err);
+        std::process::exit(1);
     }
 
-    let state = AppState {
-        show_dock: true,
-        always_on_top: false,
-        panes: im::vector![
-            PaneData { id: 0 }
-        ],
-        next_pane_id: 1,
-    };
+    let window_size = state.main_window_rect.map(|window_rect| window_rect.size())
+        .or(cli.window_size())
+        .unwrap_or(dock_window::DEFAULT_WINDOW_SIZE);
+    if cli.headless_dump_layout {
+        let pane_rects = cli::dump_layout(&state.docks[0], window_size);
+        println!("{}", serde_json::to_string_pretty(&pane_rects).expect("Pane rects are always valid JSON"));
+        return;
+    }
 
-    AppLauncher::with_window(main_window)
+    // Opened where it was last time, and then kept on the screen once the monitors are known
+    let mut main_window = dock_window::dock_window(0).window_size(window_size);
+    if let Some(window_rect) = state.main_window_rect {
+        main_window = main_window.set_position(window_rect.origin());
+    }
+
+    let launcher = AppLauncher::with_window(main_window);
+    // Kept until the app quits, which removes the socket file.
+    // Nothing is logged until the app launches, so a failure is printed instead.
+    #[cfg(unix)]
+    let _ipc_server = ipc_socket_path(&cli).and_then(|path| {
+        IpcServer::start(&path, ipc::forward_to_app(launcher.get_external_handle()))
+            .map_err(|err| eprintln!("Could not listen on {}: {}", path.display(), err))
+            .ok()
+    });
+
+    launcher
+        .delegate(Delegate { settings_path })
         .log_to_console()
         .launch(state)
         .expect("Failed to launch application");
-}
\ No newline at end of file
+}
@@ -1,656 +1,838 @@
-use druid::widget::{Widget, Flex, Label, Button, Container, LineBreaking};
+use std::collections::HashMap;
+use std::time::Duration;
+use druid::widget::Widget;
 use druid::widget::prelude::*;
-use druid::{WidgetPod, WidgetExt, Point, Color, Region, Rect};
-use crate::HEADER_HEIGHT;
-use crate::{AppState, PaneData, pane_widget::PaneWidget, pane_header_widget::PaneHeaderWidget};
+use druid::{WidgetPod, WidgetId, Point, Color, Rect, Region, Screen, Selector, SingleUse, Target, TimerToken, FontFamily};
+use druid::kurbo::{Circle, Line};
+use druid::piet::{Text, TextLayout, TextLayoutBuilder};
+use tracing::{info, trace, warn};
+use crate::{HEADER_HEIGHT, LOG_DIFFING, LOG_DRAG, LOG_LAYOUT, LOG_REPLAY};
+use crate::{AutoHide, DockData, PaneData, pane_widget::PaneWidget, pane_header_widget::PaneHeaderWidget};
+use crate::dock_history::{DockHistory, DockSnapshot, ClosedPane};
+use crate::dock_model::{DockModel, DropPreview, AUTO_HIDE_DELAY};
+use crate::dock_painting::{self, DockCanvas, DockColors};
+use crate::pane_layout;
+use crate::window_snapping::{self, SnapArea};
+use crate::event_recording::{EventRecorder, EventReplay, Recording, DEFAULT_RECORDING_PATH};
+
+pub const UNDO: Selector = Selector::new("polysoft.druid-demo.undo");
+pub const REDO: Selector = Selector::new("polysoft.druid-demo.redo");
+pub const REOPEN_CLOSED_PANE: Selector = Selector::new("polysoft.druid-demo.reopen-closed-pane");
+pub const TOGGLE_DEBUG_OVERLAY: Selector = Selector::new("polysoft.druid-demo.toggle-debug-overlay");
+pub const TOGGLE_RECORDING: Selector = Selector::new("polysoft.druid-demo.toggle-recording");
+pub const REPLAY_RECORDING: Selector = Selector::new("polysoft.druid-demo.replay-recording");
+/// Focuses the pane with the ID, and brings its window to the front. Every dock gets it, so it's sent globally.
+pub const FOCUS_PANE: Selector<i32> = Selector::new("polysoft.druid-demo.focus-pane");
+
+/// A pane being dragged out of its dock. Every dock gets these, so that the one under the pointer can take it.
+#[derive(Clone, Debug)]
+pub struct PaneDrag {
+    // The dock widget it's being dragged out of
+    pub source: WidgetId,
+    pub pane: PaneData,
+    // None when the drag was cancelled, like when its pane was closed from outside
+    pub screen_pos: Option<Point>,
+}
+
+/// Sent while a pane drag moves, so that the dock under the pointer can show where it would go.
+pub const PANE_DRAG_MOVED: Selector<PaneDrag> = Selector::new("polysoft.druid-demo.pane-drag-moved");
+/// Sent when a pane drag is released or cancelled. The dock under the pointer asks the source for the pane.
+pub const PANE_DRAG_RELEASED: Selector<PaneDrag> = Selector::new("polysoft.druid-demo.pane-drag-released");
+
+// Sent to the source dock by the dock a pane was dropped in
+struct PaneDropAccepted {
+    pane_id: i32,
+    target: WidgetId,
+    index: usize,
+}
+
+// Sent back to the dock a pane was dropped in, with the pane taken out of the source dock.
+// The widgets go with the pane, so that the state of its contents is kept.
+struct PaneTransfer {
+    pane: PaneData,
+    index: usize,
+    pane_widgets: PaneWidgets,
+}
 
+// Sent to itself when the pane widgets need to be updated with how faded they are
+const UPDATE_PANE_OPACITY: Selector = Selector::new("polysoft.druid-demo.update-pane-opacity");
+const PANE_DROP_ACCEPTED: Selector<PaneDropAccepted> = Selector::new("polysoft.druid-demo.pane-drop-accepted");
+const PANE_TRANSFERRED: Selector<SingleUse<PaneTransfer>> = Selector::new("polysoft.druid-demo.pane-transferred");
+
+// How long a dock waits for a pane it accepted before it stops making room for it
+const PANE_TRANSFER_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The panes of one dock, bound to the bound (right) side and the bottom of the space it's given.
+/// It doesn't assume it owns the window, so several can be placed in a layout, each with its own
+/// `DockData` through a lens. The window chrome, like the dock background and the input region, is up to its parent.
 pub struct PaneDockWidget {
-    dock_items: WidgetPod<AppState, Flex<AppState>>,
-    persistent_items: WidgetPod<AppState, Container<AppState>>,
-    /// Order should match order in data, not x position order.
-    // TODO: Maybe split this up. Something related to removing one of these is causing a bug.
-    panes: Vec<(PaneLocationData, WidgetPod<PaneData, PaneWidget>, WidgetPod<PaneData, PaneHeaderWidget>)>,
-    // For dragging the dock and panes
-    init_pos: Option<Point>,
-    dragging_pane: Option<usize>,
-    last_movements: Vec<f64>,
-    last_movements_index: usize,
-    total_drag_dist: f64,
+    // Where the panes are, and what is being dragged
+    model: DockModel,
+    // The part of this dock that the window's persistent items cover, which maximized panes stay below
+    persistent_items_rect: Rect,
+    /// The widgets of the panes in the data, by pane ID. The order comes from the data.
+    pane_widgets: HashMap<i32, PaneWidgets>,
+    // Widgets of panes that left the data but can still come back, like with undo.
+    // Keeping them keeps the state of the pane contents, like scroll positions and input.
+    parked_panes: HashMap<i32, PaneWidgets>,
+    // Widgets of panes that were just dropped here from another dock, until the pane is in the data
+    incoming_panes: HashMap<i32, PaneWidgets>,
+    history: DockHistory,
+    // So that changes made by undo and redo, or by panes moving between docks, are not recorded as new changes
+    is_skipping_recording: bool,
+    // Restarted by pointer activity. The dock auto-hides when the latest one fires.
+    auto_hide_timer: TimerToken,
+    // Started when a pane from another dock is accepted. If it fires before the pane arrives, the drop preview is cleared.
+    pane_transfer_timer: TimerToken,
+    // For the debug overlay
+    show_debug_overlay: bool,
+    last_frame_interval: u64,
+    // For reproducing bugs
+    recorder: Option<EventRecorder>,
+    replay: Option<EventReplay>,
+}
+
+struct PaneWidgets {
+    contents: WidgetPod<PaneData, PaneWidget>,
+    header: WidgetPod<PaneData, PaneHeaderWidget>,
+    // The env the widgets were last given while the pane was faded
+    faded_env: Option<FadedEnv>,
+}
+
+struct FadedEnv {
+    env: Env,
+    opacity: f64,
+    faded: Env,
+}
+
+impl PaneWidgets {
+    fn new() -> Self {
+        Self {
+            contents: WidgetPod::new(PaneWidget::new()),
+            header: WidgetPod::new(PaneHeaderWidget::new()),
+            faded_env: None,
+        }
+    }
+
+    // The env for the widgets, with their colors faded to the opacity of the pane.
+    // It's kept while nothing changes, since the widgets rebuild their text whenever they're given a different env.
+    fn env(&mut self, env: &Env, opacity: f64) -> Env {
+        if opacity >= 1.0 {
+            return env.clone();
+        }
+        match &self.faded_env {
+            Some(faded_env) if faded_env.opacity == opacity && faded_env.env.same(env) => faded_env.faded.clone(),
+            _ => {
+                let faded = dock_painting::pane_env(env, opacity);
+                self.faded_env = Some(FadedEnv { env: env.clone(), opacity, faded: faded.clone() });
+                faded
+            }
+        }
+    }
+}
+
+// Paints the pane widgets in between what dock_painting paints for each pane
+struct WidgetCanvas<'a, 'b, 'c, 'd> {
+    ctx: &'a mut druid::PaintCtx<'b, 'c, 'd>,
+    pane_widgets: &'a mut HashMap<i32, PaneWidgets>,
+    env: &'a druid::Env,
+}
+
+impl<'b, 'c, 'd> DockCanvas for WidgetCanvas<'_, 'b, 'c, 'd> {
+    type Render = druid::piet::Piet<'d>;
+
+    fn render_ctx(&mut self) -> &mut Self::Render {
+        self.ctx.render_ctx
+    }
+
+    fn paint_pane_widgets(&mut self, pane: &PaneData, opacity: f64) {
+        if let Some(pane_widgets) = self.pane_widgets.get_mut(&pane.id) {
+            let pane_env = pane_widgets.env(self.env, opacity);
+            pane_widgets.contents.paint(self.ctx, pane, &pane_env);
+            pane_widgets.header.paint(self.ctx, pane, &pane_env);
+        }
+    }
 }
-struct PaneLocationData {
-    // Positions are relative to the bound side. Default dist from right.
-    target_x_pos: f64, // For keeping track of where it needs to go to fit into the dock
-    actual_x_pos: f64, // For keeping track of actual location for animating it into place
-    width: f64,
-    //is_minimized: bool,
-    height: f64,
+
+impl Default for PaneDockWidget {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PaneDockWidget {
     pub fn new() -> Self {
-        let info_label = Label::new("Move and resize the pane dock, then hide the dock.");
-
-        let toggle_dock_button = Button::new("Toggle Dock")
-            .on_click(|ctx, is_shown: &mut bool, _: &Env| {
-                *is_shown = !*is_shown;
-                ctx.window().show_titlebar(*is_shown);
-                // Resize to refresh background.
-                let old_size = ctx.window().get_size();
-                let new_size = if *is_shown {
-                    // Shrink in height by 1 px
-                    Size::new(old_size.width, old_size.height - 1.0)
-                } else {
-                    // Grow in height by 1 px
-                    Size::new(old_size.width, old_size.height + 1.0)
-                };
-                ctx.window().set_size(new_size);
-                ctx.request_layout();
-            })
-            .lens(AppState::show_dock)
-            .boxed();
+        Self {
+            model: DockModel::new(),
+            persistent_items_rect: Rect::ZERO,
+            pane_widgets: HashMap::new(),
+            parked_panes: HashMap::new(),
+            incoming_panes: HashMap::new(),
+            history: DockHistory::new(),
+            is_skipping_recording: false,
+            auto_hide_timer: TimerToken::INVALID,
+            pane_transfer_timer: TimerToken::INVALID,
+            show_debug_overlay: false,
+            last_frame_interval: 0,
+            recorder: None,
+            replay: None,
+        }
+    }
 
-        let add_pane_button = Button::new("Add Pane")
-        .on_click(|ctx, data: &mut AppState, _: &Env| {
-            data.add_pane();
-            ctx.children_changed();
-        });
+    /// Sets the part of this dock, relative to it, that is covered by controls that always need to be reachable.
+    /// Maximized panes stay below it, and it is part of the input area.
+    pub fn set_persistent_items_rect(&mut self, persistent_items_rect: Rect) {
+        self.persistent_items_rect = persistent_items_rect;
+    }
 
-        let mut dock_items = Flex::row()
-            .with_child(info_label);
-        if cfg!(target_os = "linux") {
-            // Can't manually set this on Linux, but it is more often than not an option on the titlebar
-            let mut always_on_top_msg: Label<AppState> = Label::new("If the always on top button does nothing, most desktop environments allow you to set this window always on top by right clicking on the titlebar and selecting \"Always on top\"");
-            always_on_top_msg.set_line_break_mode(LineBreaking::WordWrap);
-            dock_items.add_default_spacer();
-            dock_items.add_child(always_on_top_msg);
-        }
-        let always_on_top_button = Button::new("Toggle Always On Top")
-            .on_click(|ctx, data: &mut AppState, _: &Env| {
-                data.always_on_top = !data.always_on_top;
-                println!("Setting always on top to: {}", data.always_on_top);
-                ctx.window().set_always_on_top(data.always_on_top);
-            });
-        let close_window_button = Button::new("Close Window")
-            .on_click(|ctx, _: &mut AppState, _| {
-                ctx.window().close();
-            });
-        dock_items.add_child(close_window_button);
-        dock_items.add_child(always_on_top_button);
-
-        let persistent_items = Flex::column()
-            .with_child(toggle_dock_button)
-            .with_child(add_pane_button)
-            .padding(3.0)
-            .background(Color::rgba(255.0, 255.0, 255.0, 0.1));
+    /// The parts of the dock that take input when the dock is hidden, relative to it.
+    pub fn input_area(&self) -> Region {
+        self.model.input_area()
+    }
 
-        Self {
-            dock_items: WidgetPod::new(dock_items),
-            persistent_items: WidgetPod::new(persistent_items),
-            panes: vec![],
-            init_pos: None,
-            dragging_pane: None,
-            last_movements: vec![0.0; 15 as usize], // four zero values
-            last_movements_index: 0,
-            total_drag_dist: 0.0,
-        }
-    }
-
-    /// Returns true if panes changed
-    fn update_pane_widgets(&mut self, data: &AppState, old_data: Option<&AppState>) -> bool {
-        // The situations are:
-        //  1. - 1 or more are added, ideally at the end, but it could be anywhere.
-        //  2. - 1 or more are removed, anywhere.
-        //  3. - 1 or more are added, and 1 or more are removed at the same time
-        // So we need to compare, and match all unchanged indexes,
-        // and for the changed indexes, either add panes, remove panes, or replace panes.
-
-        // Situation 1:
-        // Original: 1 2 3 4 5
-        //  Changed: 1 2 6 4 5
-        // In this situation, the 3 was replaced with 6, so the widget for 3 is removed, and added for 6.
-        // Situation 2:
-        // Original: 1 2 3
-        //  Changed: 1 2 4 3
-        // In this situation, a new widget should get created for 4, instead of creating one for 3 and shifting them.
-        // Situation 3:
-        // Original: 1 2 3
-        //  Changed: 1 3
-        // In this situation, the widget for 2 should be removed, as opposed to the one for 3.
-
-        // Plan: Iterate through both old and new data until the end is reached, individually comparing
-        // the data points for all widgets keeping track of necessary additions and deletions.
-        // This may need to be an n^2 algorithm, checking the data and then iterating iterating through
-        // the other list to find it.
-
-        // Position new panes where they need to go. The other panes will need to shift themselves to adjust
-        // Do not reposition panes next to removed panes. They will need to shift themselves.
-        match old_data {
-            Some(old_data) => {
-                let mut changed = false;
-
-                for i in (0..old_data.panes.len()).rev() { // Back to front to prevent changing the indexes
-                    // Assuming it was properly managed before, the i index will match between the old data and the widgets
-                    // Check to see if the data at index i is still there. If not, remove the data at that index
-                    let mut new_data_itr = data.panes.iter();
-                    let old_pane_data = &old_data.panes[i];
-                    let old_in_new_position = new_data_itr.position(|x| x.id == old_pane_data.id);
-                    if old_in_new_position.is_none() {
-                        println!("No pane with ID {} found in new data. Removing pane at index {}", old_pane_data.id, i);
-                        self.panes.remove(i);
-                        changed = true;
-                    } else {
-                        println!("Pane with ID {} is still in the new data.", old_pane_data.id);
-                    }
-                }
-                for old_pane_data in &old_data.panes {
-                    println!("Old data ID: {}", old_pane_data.id);
-                }
-                // For the second pass, it will be iterating, finding new ones, and adding the widgets
-                //let mut expected_old_index = 0;
-                for i in 0..data.panes.len() { // Position now relative to the desired state
-                    // While iterating, keep track of the position
-                    // If the item doesn't match an item for the old data, add new data, but do
-                    // not increment the position in the old data iterator.
-
-                    let mut old_data_itr = old_data.panes.iter();
-                    println!("Checking pane at index {i}");
-                    let new_data_item = &data.panes[i];
-
-                    // First check to see if the new data it's in the old data.
-                    // If it is not, it should be added right here as a new widget.
-                    let new_in_old_data_position = old_data_itr.position(|x| {
-                        println!("Checking old pane with ID {}. Searching for {}", x.id, new_data_item.id);
-                        x.id == new_data_item.id
-                    });
-
-                    if let Some(_new_in_old_data_position) = new_in_old_data_position {
-                        // If, for example, the first index is removed, the expected index in the old data will be 1
-                        println!("Found old pane for ID {}. Moving on.", new_data_item.id);
-                        // Existing item, so see if it was reordered.
-                        // If the item at expected_old_index does not equal the new_data_item, it was reordered.
-
-                        /*if new_in_old_data_position != expected_old_index {
-                            eprintln!("Unexpected position. Expected {}, got {}. Exiting early", new_in_old_data_position, expected_old_index);
-                        }*/
-                        // Lastly, add to the old index because this is an existing item.
-                        //expected_old_index += 1;
-                    } else {
-                        // New item, so add a widget for it
-                        println!("Could not find position of pane with ID {}. Adding new pane.", new_data_item.id);
-                        self.add_pane(i);
-                        changed = true;
-                        // Do not increment expected_old_index since it is a new addition
-                    }
-                }
-                if changed {
-                    self.refresh_all_target_positions();
-                }
-                println!("Changed: {changed}");
-                println!("Data size: {}, panes size: {}", data.panes.len(), self.panes.len());
-                return changed
-            },
-            None => {
-                // This is probably the first run
-                let panes_added = data.panes.len() - self.panes.len();
-                for _ in 0..panes_added {
-                    println!("Adding initial pane.");
-                    self.add_pane(0);
-                }
-                return panes_added != 0
+    /// Makes the pane widgets match the panes in the data.
+    /// Widgets are matched by pane ID, so a pane that moves keeps its widgets.
+    /// Widgets are only destroyed once their pane can't come back.
+    /// Returns true if panes were added or removed.
+    fn update_pane_widgets(&mut self, dock: &DockData) -> bool {
+        let changes = self.model.sync(dock);
+        if changes.is_empty() {
+            return false;
+        }
+
+        for pane_id in changes.added {
+            let pane_widgets = if let Some(parked_pane) = self.parked_panes.remove(&pane_id) {
+                trace!(target: LOG_DIFFING, "Bringing back parked pane with ID {}", pane_id);
+                parked_pane
+            } else if let Some(incoming_pane) = self.incoming_panes.remove(&pane_id) {
+                trace!(target: LOG_DIFFING, "Adding pane with ID {} from another dock", pane_id);
+                incoming_pane
+            } else {
+                trace!(target: LOG_DIFFING, "Adding pane with ID {}", pane_id);
+                PaneWidgets::new()
+            };
+            self.pane_widgets.insert(pane_id, pane_widgets);
+        }
+        for pane_id in changes.removed {
+            let removed_pane = match self.pane_widgets.remove(&pane_id) {
+                Some(removed_pane) => removed_pane,
+                None => continue,
+            };
+            if self.history.contains_pane(pane_id) {
+                trace!(target: LOG_DIFFING, "Parking pane with ID {}", pane_id);
+                self.parked_panes.insert(pane_id, removed_pane);
+            } else {
+                trace!(target: LOG_DIFFING, "Removing pane with ID {}", pane_id);
             }
         }
+        // Older history may have been dropped
+        let history = &self.history;
+        self.parked_panes.retain(|pane_id, _| history.contains_pane(*pane_id));
+        trace!(target: LOG_DIFFING, "Data size: {}, panes size: {}, parked panes: {}",
+            dock.panes.len(), self.pane_widgets.len(), self.parked_panes.len());
+        true
     }
 
-    fn get_next_position(&self) -> f64 {
-        let mut next_pos = 0.0;
-        for (pane_location_data, _pane_widget, _pane_header_widget) in &self.panes {
-            let far_edge = pane_location_data.target_x_pos + pane_location_data.width;
-            if far_edge > next_pos {
-                next_pos = far_edge;
+    fn apply_snapshot(&mut self, snapshot: DockSnapshot, dock: &mut DockData) {
+        let snapshot: DockSnapshot = snapshot.into_iter()
+            .map(|mut pane| {
+                if let Some(current_pane) = dock.panes.iter().find(|current_pane| current_pane.id == pane.id) {
+                    pane.copy_contents_from(current_pane);
+                }
+                pane
+            })
+            .collect();
+        // There is no update if nothing changes, so only skip recording if there will be one
+        self.is_skipping_recording = !dock.panes.same(&snapshot);
+        dock.panes = snapshot;
+        dock.maximized_pane = None;
+    }
+
+    fn reopen_closed_pane(&mut self, dock: &mut DockData) {
+        while let Some(closed) = self.history.take_last_closed() {
+            // It may have come back from an undo
+            if dock.panes.iter().any(|pane| pane.id == closed.pane.id) {
+                continue;
             }
+            let index = closed.index.min(dock.panes.len());
+            dock.panes.insert(index, closed.pane);
+            dock.maximized_pane = None;
+            break;
         }
-        next_pos
     }
 
-    // For finding where to place a pane when moving it
-    // It does this based on target locations, not actual locations
-    // The exclude param is to not check the pane you're repositioning
-    // It does it based on the center of the panes
-    fn get_closest_pane_to_right(&self, x_pos: f64, exclude: usize) -> Option<usize> {
-        if self.panes.len() <= 1 {
-            None
-        } else {
-            let mut closest_position = 0.0;
-            let mut closest_position_pane_index: Option<usize> = None;
-            for (i, (position_data, _pane_widget, _header_widget)) in self.panes.iter().enumerate() {
-                let pane_location_center = position_data.target_x_pos + position_data.width / 2.0;
-                if i != exclude && pane_location_center > closest_position && pane_location_center < x_pos {
-                    closest_position = pane_location_center;
-                    closest_position_pane_index = Some(i);
-                }
+    fn handle_command(&mut self, ctx: &mut druid::EventCtx, command: &druid::Command, dock: &mut DockData) {
+        if command.is(UNDO) {
+            if let Some(previous) = self.history.undo(dock.panes.clone()) {
+                self.apply_snapshot(previous, dock);
+            }
+            ctx.set_handled();
+        } else if command.is(REDO) {
+            if let Some(next) = self.history.redo(dock.panes.clone()) {
+                self.apply_snapshot(next, dock);
+            }
+            ctx.set_handled();
+        } else if command.is(REOPEN_CLOSED_PANE) {
+            self.reopen_closed_pane(dock);
+            ctx.set_handled();
+        } else if command.is(TOGGLE_RECORDING) {
+            self.toggle_recording(dock);
+            ctx.set_handled();
+        } else if command.is(REPLAY_RECORDING) {
+            self.start_replay(ctx, dock);
+            ctx.set_handled();
+        } else if command.is(UPDATE_PANE_OPACITY) {
+            ctx.request_update();
+            ctx.set_handled();
+        } else if command.is(TOGGLE_DEBUG_OVERLAY) {
+            self.show_debug_overlay = !self.show_debug_overlay;
+            // Frames keep being requested while it's shown, to keep the numbers live
+            ctx.request_anim_frame();
+            ctx.request_paint();
+            ctx.set_handled();
+        } else if let Some(pane_id) = command.get(FOCUS_PANE) {
+            if dock.panes.iter().any(|pane| pane.id == *pane_id) {
+                self.model.focus_pane(*pane_id);
+                ctx.window().bring_to_front_and_focus();
+                ctx.request_paint();
+                ctx.set_handled();
             }
-            closest_position_pane_index
+        } else if let Some(pane_drag) = command.get(PANE_DRAG_MOVED) {
+            // Every dock needs to see these, so they aren't handled
+            self.preview_pane_drop(ctx, pane_drag, dock);
+        } else if let Some(pane_drag) = command.get(PANE_DRAG_RELEASED) {
+            self.accept_pane_drop(ctx, pane_drag, dock);
+        } else if let Some(accepted) = command.get(PANE_DROP_ACCEPTED) {
+            self.transfer_pane(ctx, accepted, dock);
+            ctx.set_handled();
+        } else if let Some(transfer) = command.get(PANE_TRANSFERRED) {
+            if let Some(transfer) = transfer.take() {
+                self.receive_pane(transfer, dock);
+            }
+            ctx.set_handled();
         }
     }
 
-    fn get_new_target_x(&self, current_position: f64, exclude: usize) -> f64 {
-        if let Some(pane_to_align_to) = self.get_closest_pane_to_right(current_position, exclude) {
-            let align_pane_position_data = &self.panes[pane_to_align_to].0;
-            align_pane_position_data.target_x_pos + align_pane_position_data.width + crate::PANE_SPACING
+    // Sends the pane being dragged to every dock, with where the pointer is on the screen
+    fn submit_pane_drag(&self, ctx: &mut druid::EventCtx, selector: Selector<PaneDrag>, pos: Point, dock: &DockData) {
+        let pane = match self.model.dragging_pane().and_then(|index| dock.panes.get(index)) {
+            Some(pane) => pane.clone(),
+            None => return,
+        };
+        let pane_drag = PaneDrag { source: ctx.widget_id(), pane, screen_pos: Some(ctx.to_screen(pos)) };
+        ctx.submit_command(selector.with(pane_drag).to(Target::Global));
+    }
+
+    // Tells the other docks that the pane drag is over without a drop, so that none keeps making room for it
+    fn submit_pane_drag_cancelled(&self, ctx: &mut druid::UpdateCtx, pane: PaneData) {
+        trace!(target: LOG_DRAG, "Pane drag of the pane with ID {} was cancelled", pane.id);
+        let pane_drag = PaneDrag { source: ctx.widget_id(), pane, screen_pos: None };
+        ctx.submit_command(PANE_DRAG_RELEASED.with(pane_drag).to(Target::Global));
+    }
+
+    // Where a pane drag from another dock is within this dock, if it's over it
+    fn pane_drag_pos(&self, ctx: &druid::EventCtx, pane_drag: &PaneDrag) -> Option<Point> {
+        if pane_drag.source == ctx.widget_id() {
+            return None;
+        }
+        let pos = (pane_drag.screen_pos? - ctx.to_screen(Point::ZERO)).to_point();
+        if ctx.size().to_rect().contains(pos) {
+            Some(pos)
         } else {
-            crate::PANE_SPACING
-        }
-    }
-
-    // Makes sure that all panes that are at or left of moved_index are correctly to the left
-    fn shift_left_of(&mut self, moved_index: usize) {
-        // First find the left position
-        let moved_pane_location_data = &self.panes[moved_index].0;
-        let moved_pane_right_x = moved_pane_location_data.target_x_pos;
-        let moved_pane_left_x = moved_pane_right_x + moved_pane_location_data.width;
-        let mut shift_amount = 0.0;
-        for i in 0..self.panes.len() {
-            if i != moved_index {
-                let pane_location = self.panes[i].0.target_x_pos;
-                if pane_location >= moved_pane_right_x && pane_location < moved_pane_left_x {
-                    let needed_shift = moved_pane_left_x - pane_location;
-                    if needed_shift >= shift_amount {
-                        shift_amount = needed_shift;
-                    }
-                }
-            }
+            None
         }
-        // Now shift everything at or left of the moved pane
-        if shift_amount > 0.0 {
-            for i in 0..self.panes.len() {
-                if i != moved_index {
-                    let pane_location = self.panes[i].0.target_x_pos;
-                    if pane_location >= moved_pane_right_x {
-                        self.panes[i].0.target_x_pos += shift_amount;
-                    }
+    }
+
+    fn preview_pane_drop(&mut self, ctx: &mut druid::EventCtx, pane_drag: &PaneDrag, dock: &DockData) {
+        let drop_preview = self.pane_drag_pos(ctx, pane_drag).map(|pos| DropPreview {
+            index: self.model.drop_index_at(pos, dock),
+            width: pane_drag.pane.width,
+            height: pane_drag.pane.height,
+        });
+        if self.model.set_drop_preview(drop_preview) {
+            // The panes move to make room
+            ctx.request_anim_frame();
+            ctx.request_paint();
+        }
+    }
+
+    // Asks the dock the pane is being dragged out of for it, if it was released over this dock
+    fn accept_pane_drop(&mut self, ctx: &mut druid::EventCtx, pane_drag: &PaneDrag, dock: &DockData) {
+        let pos = match self.pane_drag_pos(ctx, pane_drag) {
+            Some(pos) => pos,
+            None => {
+                if self.model.set_drop_preview(None) {
+                    ctx.request_anim_frame();
+                    ctx.request_paint();
                 }
+                return;
+            }
+        };
+        // The preview stays until the pane arrives, so that the panes don't move back and forth
+        let index = self.model.drop_index_at(pos, dock);
+        trace!(target: LOG_DRAG, "Accepting pane with ID {} from another dock at index {}", pane_drag.pane.id, index);
+        let accepted = PaneDropAccepted {
+            pane_id: pane_drag.pane.id,
+            target: ctx.widget_id(),
+            index,
+        };
+        ctx.submit_command(PANE_DROP_ACCEPTED.with(accepted).to(pane_drag.source));
+        // The source may not have the pane anymore, or be gone itself
+        self.pane_transfer_timer = ctx.request_timer(PANE_TRANSFER_TIMEOUT);
+    }
+
+    // Takes a pane that was dropped in another dock out of this one, and sends it there with its widgets
+    fn transfer_pane(&mut self, ctx: &mut druid::EventCtx, accepted: &PaneDropAccepted, dock: &mut DockData) {
+        let index = match dock.panes.iter().position(|pane| pane.id == accepted.pane_id) {
+            Some(index) => index,
+            None => return,
+        };
+        trace!(target: LOG_DRAG, "Moving pane with ID {} to another dock", accepted.pane_id);
+        let pane = dock.panes[index].clone();
+        dock.close_pane(index);
+        // It isn't closed, so it can't be brought back here
+        self.history.forget_pane(pane.id);
+        self.is_skipping_recording = true;
+
+        let pane_widgets = self.pane_widgets.remove(&pane.id).unwrap_or_else(PaneWidgets::new);
+        let transfer = PaneTransfer { pane, index: accepted.index, pane_widgets };
+        ctx.submit_command(PANE_TRANSFERRED.with(SingleUse::new(transfer)).to(accepted.target));
+    }
+
+    fn receive_pane(&mut self, transfer: PaneTransfer, dock: &mut DockData) {
+        let PaneTransfer { pane, index, pane_widgets } = transfer;
+        // Undoing here shouldn't lose it
+        self.history.adopt_pane(&pane);
+        self.is_skipping_recording = true;
+        self.model.set_drop_preview(None);
+        self.pane_transfer_timer = TimerToken::INVALID;
+        self.incoming_panes.insert(pane.id, pane_widgets);
+        let index = index.min(dock.panes.len());
+        dock.panes.insert(index, pane);
+        dock.maximized_pane = None;
+    }
+
+    fn toggle_recording(&mut self, dock: &DockData) {
+        if let Some(recorder) = self.recorder.take() {
+            let recording = recorder.finish(&dock.panes);
+            match recording.save(DEFAULT_RECORDING_PATH) {
+                Ok(()) => info!(target: LOG_REPLAY, "Saved {} events to {}", recording.events.len(), DEFAULT_RECORDING_PATH),
+                Err(err) => warn!(target: LOG_REPLAY, "Could not save the recording to {}: {}", DEFAULT_RECORDING_PATH, err),
             }
+        } else if self.replay.is_none() {
+            info!(target: LOG_REPLAY, "Started recording");
+            self.recorder = Some(EventRecorder::new(self.model.dock_size(), dock));
         }
     }
 
-    // This refreshes them based on the centers of the panes
-    fn refresh_all_target_positions(&mut self) {
-        if self.panes.len() == 0 {
+    fn start_replay(&mut self, ctx: &mut druid::EventCtx, dock: &mut DockData) {
+        if self.recorder.is_some() || self.replay.is_some() {
             return;
         }
-        // Make sure the rightmost one is against the right. If not, shift them all
-        let correct_right = crate::PANE_SPACING;
-        let mut actual_rightmost = self.panes[0].0.target_x_pos;
-        for i in 1..self.panes.len() {
-            let existing_pane_pos = self.panes[i].0.target_x_pos;
-            if existing_pane_pos < actual_rightmost {
-                actual_rightmost = existing_pane_pos;
+        let recording = match Recording::load(DEFAULT_RECORDING_PATH) {
+            Ok(recording) => recording,
+            Err(err) => {
+                warn!(target: LOG_REPLAY, "Could not load the recording from {}: {}", DEFAULT_RECORDING_PATH, err);
+                return;
             }
+        };
+        let dock_size = self.model.dock_size();
+        if recording.dock_size != (dock_size.width, dock_size.height) {
+            warn!(target: LOG_REPLAY, "The recording was made with a dock size of {:?}, but it is now {:?}. It may not replay the same.",
+                recording.dock_size, dock_size);
+        }
+        recording.apply_initial_state(dock);
+        // Cancel anything in progress
+        self.model.cancel_pointer();
+        ctx.set_active(false);
+        info!(target: LOG_REPLAY, "Replaying {} events", recording.events.len());
+        self.replay = Some(EventReplay::new(recording));
+        ctx.request_anim_frame();
+    }
+
+    // Replays the events that happened up to the next animation frame in the recording.
+    // They go through the same handling as real input, except that dragging the window doesn't move it.
+    fn replay_next_frame(&mut self, ctx: &mut druid::EventCtx, dock: &mut DockData, env: &druid::Env) {
+        let (frame_events, finished) = match &mut self.replay {
+            Some(replay) => (replay.next_frame(), replay.is_finished()),
+            None => return,
+        };
+        for event in &frame_events {
+            self.handle_event(ctx, event, dock, env);
         }
-        let right_align_diff = actual_rightmost - correct_right;
-        if right_align_diff > 0.0 {
-            for i in 0..self.panes.len() {
-                let pane_position_data = &mut self.panes[i].0;
-                pane_position_data.target_x_pos -= right_align_diff;
+        if finished {
+            if let Some(replay) = self.replay.take() {
+                let expected_panes = replay.final_panes.into_iter().collect();
+                if pane_layout::layout_changed(&expected_panes, &dock.panes) {
+                    warn!(target: LOG_REPLAY, "Replay finished with a different layout than the recording");
+                } else {
+                    info!(target: LOG_REPLAY, "Replay finished with the same layout as the recording");
+                }
             }
         }
+        // Keep going until the replay is finished, and then let the panes settle
+        ctx.request_anim_frame();
+    }
 
-        // Now align them all
-        for i in 0..self.panes.len() {
-            let position_data = &self.panes[i].0;
-            let center_x = position_data.target_x_pos + position_data.width / 2.0;
-            let new_target_x = self.get_new_target_x(center_x, i);
-            let is_moved = new_target_x != position_data.target_x_pos;
-            (&mut self.panes[i].0).target_x_pos = new_target_x;
-            //if self.dragging_pane.is_none() || self.dragging_pane.unwrap() != i { // Don't snap the currently dragged one
-            //    (&mut self.panes[i].0).actual_x_pos = new_target_x;
-            //}
-            // Now move all panes that aren't at index i that have an equal or greater position to left, if needed.
-            // First check if there is an overlapping pane
-            let mut has_overlapping = false;
-            for j in 0..self.panes.len() {
-                if j != i {
-                    //println!("j : {}, j-x: {}, i-x: {}", j, self.panes[j].0.target_x_pos, new_target_x);
+    // Changes are recorded when the data changes, since panes can be opened and closed from outside the dock
+    fn record_pane_changes(&mut self, old_dock: &DockData, dock: &DockData) {
+        for (index, old_pane) in old_dock.panes.iter().enumerate() {
+            if !dock.panes.iter().any(|pane| pane.id == old_pane.id) {
+                self.history.record_closed(ClosedPane { pane: old_pane.clone(), index });
+            }
+        }
+        self.history.record(old_dock.panes.clone());
+    }
+
+    fn handle_auto_hide(&mut self, ctx: &mut druid::EventCtx, event: &druid::Event, dock: &DockData) {
+        match event {
+            Event::MouseDown(me) | Event::MouseUp(me) | Event::MouseMove(me) | Event::Wheel(me) => {
+                if self.model.pointer_activity(me.pos) {
+                    ctx.request_anim_frame();
                 }
-                if j != i && self.panes[j].0.target_x_pos == new_target_x {
-                    has_overlapping = true;
-                    break;
+                if dock.auto_hide != AutoHide::Off {
+                    self.auto_hide_timer = ctx.request_timer(AUTO_HIDE_DELAY);
                 }
             }
-            if has_overlapping {
-                //println!("HAS OVERLAPPING");
-                // Shift all panes to the left width display units to left
-                let move_amount = self.panes[i].0.width + crate::PANE_SPACING;
-                for j in 0..self.panes.len() {
-                    self.panes[i].0.target_x_pos;
-                    if j != i && self.panes[j].0.target_x_pos >= new_target_x {
-                        self.panes[j].0.target_x_pos += move_amount;
-                    }
+            Event::Timer(token) if *token == self.auto_hide_timer => {
+                if self.model.hide_if_idle(dock) {
+                    ctx.request_anim_frame();
                 }
             }
+            _ => (),
         }
     }
 
-    fn add_pane(&mut self, position: usize) {
-        let next_pos = self.get_next_position() + crate::PANE_SPACING;
-        let new_loc_data = PaneLocationData {
-            height: crate::DEFAULT_PANE_HEIGHT,
-            width: crate::DEFAULT_PANE_WIDTH,
-            target_x_pos: next_pos,
-            actual_x_pos: next_pos,
-        };
-        let new_content_widget = WidgetPod::new(PaneWidget::new());
-        let new_header_widget = WidgetPod::new(PaneHeaderWidget::new());
-        let new_item = (new_loc_data, new_content_widget, new_header_widget);
-        self.panes.insert(position, new_item);
-    }
-
-    fn handle_dragging(&mut self, ctx: &mut druid::EventCtx, event: &druid::Event, data: &mut AppState) {
+    fn handle_dragging(&mut self, ctx: &mut druid::EventCtx, event: &druid::Event, dock: &mut DockData) {
         match event {
             Event::MouseDown(me) if me.buttons.has_left() => {
-                self.total_drag_dist = 0.0;
-                if self.get_input_area().contains(me.window_pos) {
-                    // Now check to see if it's within a pane header
-                    if let Some(widget_index) = self.find_pane(me.window_pos) {
-                        let (_location_data, _contents, header) = &self.panes[widget_index];
-                        if header.layout_rect().contains(me.window_pos) {
-                            ctx.set_active(true);
-                            self.init_pos = Some(me.window_pos);
-                            self.dragging_pane = Some(widget_index);
-                            // Start animating
-                            ctx.request_anim_frame();
-                        }
-                    }
-                } else {
+                if self.model.pointer_down(me.pos, me.count, dock) {
                     ctx.set_active(true);
-                    self.init_pos = Some(me.window_pos);
-                    self.dragging_pane = None;
+                    // Start animating
+                    ctx.request_anim_frame();
                 }
             }
             Event::MouseMove(me) if ctx.is_active() && me.buttons.has_left() => {
-                if let Some(init_pos) = self.init_pos {
-                    let within_window_change = me.window_pos.to_vec2() - init_pos.to_vec2();
-                    // Could either be dragging a pane or the entire window
-                    if let Some(dragging_pane) = self.dragging_pane {
-                        // Dragging pane
-                        let location_data = &mut self.panes[dragging_pane].0;
-                        // Move the pane's actual position based on the change
-                        location_data.actual_x_pos -= within_window_change.x;
-                        self.total_drag_dist += within_window_change.x.abs();
-                        // Know if moving right, since the threshold changes between moving left and right
-                        // Add the newest movement, and increment the circular index
-                        if within_window_change.x.abs() > 1.0 {
-                            self.last_movements[self.last_movements_index] = within_window_change.x;
-                            self.last_movements_index += 1;
-                            self.last_movements_index %= self.last_movements.len();
-                        }
-
-                        let mut total_recent_movement = 0.0;
-                        for i in self.last_movements.iter() {
-                            total_recent_movement += i;
-                        }
-                        let is_moving_right = total_recent_movement > 0.0;
-                        // See if there is a new target location for this pane
-                        // If so, adjust the target location.
-                        let current_x = if is_moving_right {
-                            location_data.actual_x_pos - location_data.width * 0.25
-                        } else {
-                            location_data.actual_x_pos + location_data.width * 1.25
-                        };
-                        let new_target_x = self.get_new_target_x(current_x, dragging_pane);
-                        let location_data = &mut self.panes[dragging_pane].0;
-                        location_data.target_x_pos = new_target_x;
-                        // Make sure everything left is actually left of it
-                        self.shift_left_of(dragging_pane);
-                        // Refresh all
-                        self.refresh_all_target_positions();
-
-                        self.init_pos = Some(me.window_pos); // Move it to prevent compounding changes
-                        ctx.request_layout();
-                    } else {
-                        // Dragging window
-                        let old_pos = ctx.window().get_position();
-                        let new_pos = old_pos + within_window_change;
-
-                        ctx.window().set_position(new_pos)
+                if let Some(window_change) = self.model.pointer_move(me.pos, dock) {
+                    // Dragging window, which a replay leaves where it is
+                    if self.replay.is_some() {
+                        trace!(target: LOG_REPLAY, "Not moving the window by {:?} for the replay", window_change);
+                        return;
+                    }
+                    let old_pos = ctx.window().get_position();
+                    let mut new_pos = old_pos + window_change;
+                    // Holding shift moves it freely
+                    if !me.mods.shift() {
+                        let areas: Vec<SnapArea> = Screen::get_monitors().iter()
+                            .map(|monitor| SnapArea { bounds: monitor.virtual_rect(), work_area: monitor.virtual_work_rect() })
+                            .collect();
+                        new_pos = window_snapping::snap_window_position(new_pos, ctx.window().get_size(), &areas);
+                    }
+
+                    ctx.window().set_position(new_pos)
+                } else {
+                    if self.model.is_pane_drag_started() {
+                        // Another dock may be under the pointer
+                        self.submit_pane_drag(ctx, PANE_DRAG_MOVED, me.pos, dock);
                     }
+                    ctx.request_layout();
                 }
             }
             Event::MouseUp(me) if ctx.is_active() => {
-                // Check to see if it was in the close button
-                if let Some(dragging_pane) = self.dragging_pane {
-
-                    // Check to see if it was on the close button
-                    let (_location_data, _contents, header) = &mut self.panes[dragging_pane];
-                    let header_position = header.layout_rect();
-                    let relative_position = Point::new(me.window_pos.x - header_position.x0, me.window_pos.y - header_position.y0);
-
-                    if self.total_drag_dist < 1.0 && header.widget().is_in_button(relative_position) {
-                        data.panes.remove(dragging_pane);
-                        self.refresh_all_target_positions();
-                        ctx.request_update();
-                        ctx.request_layout();
-                    } else {
-                        // Finalize the position
-                        self.dragging_pane = None;
-                        self.refresh_all_target_positions();
-                        ctx.request_layout();
-                    }
+                if self.model.is_pane_drag_started() {
+                    // If it's over another dock, that dock asks for the pane
+                    self.submit_pane_drag(ctx, PANE_DRAG_RELEASED, me.pos, dock);
+                }
+                if let Some(drag_start_panes) = self.model.pointer_up(me.pos, dock) {
+                    self.history.record(drag_start_panes);
                 }
-                // Reset everything
-                self.init_pos = None;
-                self.dragging_pane = None;
-                self.total_drag_dist = 0.0;
+                // The dragged pane animates into place
+                ctx.request_anim_frame();
+                ctx.request_layout();
                 ctx.set_active(false)
             }
             Event::AnimFrame(time_since_last_frame) => {
-                // TODO: use time_since_last_frame to adjust to different frame rates
-                // Check if any panes need animating. If they do, request another AnimFrame
-                let expected_time = 16000000.0;
-                let time_relative_to_reference = *time_since_last_frame as f64 / expected_time;
-                let anim_speed_correction = time_relative_to_reference.max(0.25).min(1.25);
-                let mut animation_needed = false;
-                let mut layout_needed = false;
-                let min_animation_speed = 20.0 * anim_speed_correction;
-                for i in 0..self.panes.len() {
-                    if !self.dragging_pane.is_none() && self.dragging_pane.unwrap() == i {
-                        // Skip dragged pane until it's no longer being dragged
-                        continue;
-                    }
-                    let existing_pane_location_data = &mut self.panes[i].0;
-                    let location_diff = existing_pane_location_data.target_x_pos - existing_pane_location_data.actual_x_pos;
-                    if location_diff.abs() > 0.0 {
-                        let move_amount = if location_diff.abs() < min_animation_speed {
-                            location_diff
-                        } else {
-                            let mut move_amount_magnitude = min_animation_speed;
-                            
-                            move_amount_magnitude += location_diff.abs() * 0.15 * anim_speed_correction;
-                            if location_diff.is_sign_negative() {
-                                move_amount_magnitude *= -1.0;
-                            }
-                            move_amount_magnitude
-                        };
-                        existing_pane_location_data.actual_x_pos += move_amount;
-                        if !layout_needed {
-                            layout_needed = true;
-                        }
-
-                        // Now determine if more change is required
-                        if !animation_needed {
-                            let new_location_diff = existing_pane_location_data.target_x_pos - existing_pane_location_data.actual_x_pos;
-                            if new_location_diff.abs() > 0.0 {
-                                animation_needed = true;
-                            }
-                        }
-                    }
-                }
-                // No changes may be needed except for the currently dragged pane,
-                // so keep requesting frames until dragging is complete.
-                if animation_needed || self.dragging_pane.is_some() {
+                self.last_frame_interval = *time_since_last_frame;
+                let layout_needed = self.model.animate(*time_since_last_frame, dock);
+                if self.model.is_animating() || self.show_debug_overlay {
                     ctx.request_anim_frame();
                 }
+                if self.show_debug_overlay {
+                    ctx.request_paint();
+                }
                 if layout_needed {
                     ctx.request_layout();
                 }
             }
+            Event::Timer(token) if *token == self.pane_transfer_timer => {
+                // The accepted pane never arrived
+                trace!(target: LOG_DRAG, "No pane arrived after accepting a drop");
+                self.pane_transfer_timer = TimerToken::INVALID;
+                if self.model.set_drop_preview(None) {
+                    ctx.request_anim_frame();
+                    ctx.request_paint();
+                }
+            }
             _ => (),
         }
     }
 
-    fn get_input_area(&self) -> Region {
-        let mut interactable_area = Region::EMPTY;
-        interactable_area.add_rect(self.persistent_items.layout_rect());
-        let panes_itr = self.panes.iter();
-        for (_, pane_widget, header_widget) in panes_itr {
-            interactable_area.add_rect(pane_widget.layout_rect());
-            interactable_area.add_rect(header_widget.layout_rect());
+    /// Draws the layout targets, the positions used for dragging, and the input region.
+    fn paint_debug_overlay(&self, ctx: &mut druid::PaintCtx, dock: &DockData) {
+        let target_color = Color::rgb8(255, 200, 0);
+        let actual_color = Color::rgb8(0, 200, 255);
+        let drop_color = Color::rgb8(255, 50, 50);
+        let input_area_color = Color::rgba8(0, 255, 0, 150);
+        let model = &self.model;
+        let dock_size = model.dock_size();
+
+        // This dock's part of the region handed to set_input_region when the dock is hidden
+        for rect in model.input_area().rects() {
+            ctx.stroke(rect.inset(-1.0), &input_area_color, 2.0);
+        }
+
+        let targets = model.targets(dock);
+        // Where the dragged pane will land, which is the same as where the model moves it to
+        let drop_target = model.drop_target(dock);
+        let mut lines = vec![];
+        for (i, (pane, target)) in model.panes().iter().zip(targets.iter()).enumerate() {
+            let target_rect = Rect::new(
+                model.to_window_x(target.x_pos + target.width),
+                dock_size.height - target.height - HEADER_HEIGHT + target.y_offset,
+                model.to_window_x(target.x_pos),
+                dock_size.height + target.y_offset,
+            );
+            let target_stroke_width = if drop_target == Some(i) { 4.0 } else { 1.0 };
+            let target_stroke_color = if drop_target == Some(i) { &drop_color } else { &target_color };
+            ctx.stroke(target_rect, target_stroke_color, target_stroke_width);
+            let header_rect = model.header_rect(i);
+            ctx.stroke(header_rect.union(model.contents_rect(i)), &actual_color, 1.0);
+
+            // The centers that the dragged pane is compared against
+            let target_center = Point::new(model.to_window_x(target.x_pos + target.width / 2.0), header_rect.center().y);
+            ctx.fill(Circle::new(target_center, 4.0), &target_color);
+
+            lines.push(format!("Pane {} (ID {}): target x {:.1}, actual x {:.1}",
+                i, pane.pane_id, target.x_pos, pane.actual_x_pos));
+        }
+
+        if let Some(drop_x_pos) = model.drop_x_pos() {
+            let window_x = model.to_window_x(drop_x_pos);
+            ctx.stroke(Line::new((window_x, 0.0), (window_x, dock_size.height)), &drop_color, 2.0);
+        }
+
+        let fps = if self.last_frame_interval > 0 {
+            1_000_000_000.0 / self.last_frame_interval as f64
+        } else {
+            0.0
         };
-        interactable_area
+        lines.insert(0, format!("FPS: {:.1}, drag velocity: {:.1}, dragging: {:?}, drop target: {:?}",
+            fps, model.drag_velocity(), model.dragging_pane(), drop_target));
+
+        let text = ctx.text().new_text_layout(lines.join("\n"))
+            .font(FontFamily::MONOSPACE, 12.0)
+            .text_color(Color::WHITE)
+            .build();
+        if let Ok(text) = text {
+            let text_origin = Point::new(4.0, self.persistent_items_rect.y1 + 4.0);
+            ctx.fill(Rect::from_origin_size(text_origin, text.size()), &Color::rgba8(0, 0, 0, 180));
+            ctx.draw_text(&text, text_origin);
+        }
     }
+}
 
-    fn find_pane(&self, point: Point) -> Option<usize> {
-        for (i, (_, pane_widget, header_widget)) in self.panes.iter().enumerate() {
-            if pane_widget.layout_rect().contains(point) || header_widget.layout_rect().contains(point) {
-                return Some(i);
+impl PaneDockWidget {
+    fn handle_event(&mut self, ctx: &mut druid::EventCtx, event: &druid::Event, dock: &mut DockData, env: &druid::Env) {
+        for pane_data in dock.panes.iter_mut() {
+            let opacity = self.pane_opacity(pane_data.id, env);
+            if let Some(pane_widgets) = self.pane_widgets.get_mut(&pane_data.id) {
+                let pane_env = pane_widgets.env(env, opacity);
+                pane_widgets.contents.event(ctx, event, pane_data, &pane_env);
+                pane_widgets.header.event(ctx, event, pane_data, &pane_env);
             }
         };
-        None
-    }
-}
 
-impl Widget<AppState> for PaneDockWidget {
-    fn event(&mut self, ctx: &mut druid::EventCtx, event: &druid::Event, data: &mut AppState, env: &druid::Env) {
-        self.dock_items.event(ctx, event, data, env);
-        self.persistent_items.event(ctx, event, data, env);
+        if let Event::Command(command) = event {
+            self.handle_command(ctx, command, dock);
+        }
+        // Before dragging, so that a press on the strip of a hidden dock brings it back up
+        self.handle_auto_hide(ctx, event, dock);
+        self.handle_dragging(ctx, event, dock);
+        self.handle_pane_highlighting(ctx, event, dock);
+    }
 
-        let panes_iter = self.panes.iter_mut();
-        let data_iter = data.panes.iter_mut();
-        for ((_pane_location_data,pane_widget, header_widget), pane_data) in panes_iter.zip(data_iter) {
-            pane_widget.event(ctx, event, pane_data, env);
-            header_widget.event(ctx, event, pane_data, env);
-        };
+    // How opaque the pane and its widgets are
+    fn pane_opacity(&self, pane_id: i32, env: &druid::Env) -> f64 {
+        DockColors::from_env(env).pane_opacity(self.model.is_pane_idle(pane_id))
+    }
 
-        self.handle_dragging(ctx, event, data);
+    // Idle panes can be faded, and unfocused ones dimmed
+    fn handle_pane_highlighting(&mut self, ctx: &mut druid::EventCtx, event: &druid::Event, dock: &DockData) {
+        match event {
+            Event::MouseMove(me) => {
+                if self.model.pointer_hover(Some(me.pos), dock) {
+                    // The pane widgets get their faded colors in an update
+                    ctx.request_update();
+                    ctx.request_paint();
+                }
+            }
+            // The model focuses the pressed pane
+            Event::MouseDown(_) => ctx.request_paint(),
+            _ => (),
+        }
     }
+}
 
-    fn lifecycle(&mut self, ctx: &mut druid::LifeCycleCtx, event: &druid::LifeCycle, data: &AppState, env: &druid::Env) {
-        self.dock_items.lifecycle(ctx, event, data, env);
-        self.persistent_items.lifecycle(ctx, event, data, env);
+impl Widget<DockData> for PaneDockWidget {
+    fn event(&mut self, ctx: &mut druid::EventCtx, event: &druid::Event, data: &mut DockData, env: &druid::Env) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(event);
+        }
+        if self.replay.is_some() {
+            match event {
+                // Real input would change the result of the replay
+                Event::MouseDown(_) | Event::MouseUp(_) | Event::MouseMove(_) | Event::Wheel(_)
+                    | Event::KeyDown(_) | Event::KeyUp(_) => return,
+                Event::AnimFrame(_) => {
+                    self.replay_next_frame(ctx, data, env);
+                    return;
+                }
+                _ => (),
+            }
+        }
+        self.handle_event(ctx, event, data, env);
+    }
 
+    fn lifecycle(&mut self, ctx: &mut druid::LifeCycleCtx, event: &druid::LifeCycle, data: &DockData, env: &druid::Env) {
         if let LifeCycle::WidgetAdded = event {
             // When added, make sure all existing panes are accounted for
-            println!("Widget Added Event");
-            if self.update_pane_widgets(data, None) {
-                
+            trace!(target: LOG_DIFFING, "Widget added with {} panes", data.panes.len());
+            if self.update_pane_widgets(data) {
                 ctx.children_changed();
                 ctx.request_layout();
-                println!("Children changed and layout requested.");
+            }
+            if data.auto_hide != AutoHide::Off {
+                self.auto_hide_timer = ctx.request_timer(AUTO_HIDE_DELAY);
+            }
+        }
+        if let LifeCycle::HotChanged(false) = event {
+            // The pointer left, so no pane is hovered
+            if self.model.pointer_hover(None, data) {
+                // There is no update from here, so it's asked for with an event
+                ctx.submit_command(UPDATE_PANE_OPACITY.to(ctx.widget_id()));
+                ctx.request_paint();
             }
         }
 
-        //println!("Pane data length: {}, Pane widget length: {}", data.panes.len(), self.panes.len());
-        let panes_iter = self.panes.iter_mut();
-        let data_iter = data.panes.iter();
-        for ((_pane_location_data,pane_widget, header_widget), pane_data) in panes_iter.zip(data_iter) {
-            header_widget.lifecycle(ctx, event, pane_data, env);
-            pane_widget.lifecycle(ctx, event, pane_data, env);
+        for pane_data in data.panes.iter() {
+            let opacity = self.pane_opacity(pane_data.id, env);
+            if let Some(pane_widgets) = self.pane_widgets.get_mut(&pane_data.id) {
+                let pane_env = pane_widgets.env(env, opacity);
+                pane_widgets.header.lifecycle(ctx, event, pane_data, &pane_env);
+                pane_widgets.contents.lifecycle(ctx, event, pane_data, &pane_env);
+            }
         };
     }
 
-    fn update(&mut self, ctx: &mut druid::UpdateCtx, old_data: &AppState, data: &AppState, env: &druid::Env) {
-        let panes_iter = self.panes.iter_mut();
-        let data_iter = data.panes.iter();
-        for ((_pane_location_data,pane_widget, header_widget), pane_data) in panes_iter.zip(data_iter) {
-            header_widget.update(ctx, pane_data, env);
-            pane_widget.update(ctx, pane_data, env);
-        };
+    fn update(&mut self, ctx: &mut druid::UpdateCtx, old_data: &DockData, data: &DockData, env: &druid::Env) {
+        // Update the existing widgets before any are added, since new widgets need to be added first
+        for pane_data in data.panes.iter() {
+            let opacity = self.pane_opacity(pane_data.id, env);
+            if let Some(pane_widgets) = self.pane_widgets.get_mut(&pane_data.id) {
+                let pane_env = pane_widgets.env(env, opacity);
+                pane_widgets.header.update(ctx, pane_data, &pane_env);
+                pane_widgets.contents.update(ctx, pane_data, &pane_env);
+            }
+        }
+
+        if ctx.env_changed() {
+            // The colors or opacities may have changed
+            ctx.request_paint();
+        }
 
-        if self.update_pane_widgets(data, Some(old_data)) || old_data.show_dock != data.show_dock {
+        let layout_changed = pane_layout::layout_changed(&old_data.panes, &data.panes);
+        // Pane drags are recorded once they're finished
+        if layout_changed && !self.is_skipping_recording && self.model.dragging_pane().is_none() {
+            self.record_pane_changes(old_data, data);
+        }
+        self.is_skipping_recording = false;
+
+        // Syncing cancels a pane drag when the pane was closed from outside
+        let dragged_pane = self.model.dragging_pane()
+            .filter(|_| self.model.is_pane_drag_started())
+            .map(|index| self.model.panes()[index].pane_id)
+            .and_then(|pane_id| old_data.panes.iter().find(|pane| pane.id == pane_id))
+            .cloned();
+        if self.update_pane_widgets(data) {
             ctx.children_changed();
             ctx.request_layout();
         }
-    }
-
-    fn layout(&mut self, ctx: &mut druid::LayoutCtx, bc: &druid::BoxConstraints, data: &AppState, env: &druid::Env) -> druid::Size {
-        //println!("Layout");
-        let inner_item_bc = BoxConstraints::new(Size::new(0.0, 0.0), bc.max());
-        
-        // Position to right
-        let persistent_items_size = self.persistent_items.layout(ctx, &inner_item_bc, data, env);
-        self.persistent_items.set_origin(ctx, Point::new(bc.max().width - persistent_items_size.width, 0.0));
-
-        if data.show_dock {
-            let dock_item_bc = BoxConstraints::new(inner_item_bc.min(),
-                Size::new(inner_item_bc.max().width - persistent_items_size.width, inner_item_bc.max().height));
-            let _dock_items_layout = self.dock_items.layout(ctx, &dock_item_bc, data, env);
-            self.dock_items.set_origin(ctx, Point::new(0.0, 0.0));
-        }
-
-        let panes_iter = self.panes.iter_mut();
-        let data_iter = data.panes.iter();
-        for ((pane_location_data, pane_widget, header_widget), pane_data) in panes_iter.zip(data_iter) {
-            let pane_expected_size = Size::new(pane_location_data.width, pane_location_data.height);
-            let pane_bc = BoxConstraints::new(pane_expected_size, pane_expected_size);
-            let pane_size = pane_widget.layout(ctx, &pane_bc, pane_data, env);
-            // align to bottom
-            pane_widget.set_origin(ctx, Point::new(
-                // Shift X by 1 for border
-                bc.max().width - pane_location_data.actual_x_pos - pane_location_data.width + 1.0,
-                bc.max().height - pane_size.height
-            ));
-            // Position header
-            let header_expected_size = Size::new(pane_location_data.width, HEADER_HEIGHT);
-            let header_bc = BoxConstraints::new(header_expected_size, header_expected_size);
-            let header_size = header_widget.layout(ctx, &header_bc, pane_data, env);
-            // align to top of content
-            header_widget.set_origin(ctx, Point::new(
-                // Shift x by one for border
-                bc.max().width - pane_location_data.actual_x_pos - pane_location_data.width + 1.0,
-                bc.max().height - pane_size.height - header_size.height
-            ));
-        };
-        if data.show_dock {
-            ctx.window().set_input_region(None);
-        } else {
-            ctx.window().set_input_region(Some(self.get_input_area()));
+        if let Some(pane) = dragged_pane {
+            if !self.model.is_pane_drag_started() {
+                self.submit_pane_drag_cancelled(ctx, pane);
+            }
         }
 
-        bc.max()
-    }
+        if old_data.auto_hide != data.auto_hide {
+            // Start counting from the change, hiding to wherever the new mode goes
+            if data.auto_hide == AutoHide::Off {
+                self.model.reveal();
+            } else {
+                self.auto_hide_timer = ctx.request_timer(AUTO_HIDE_DELAY);
+            }
+            self.model.start_animating();
+            ctx.request_anim_frame();
+        }
 
-    fn paint(&mut self, ctx: &mut druid::PaintCtx, data: &AppState, env: &druid::Env) {
-        if data.show_dock {
-            // Paint background
-            let paint_area = ctx.region().bounding_box();
-            ctx.fill(paint_area, &env.get(crate::DOCK_BACKGROUND_COLOR_KEY));
-            // Paint dock items
-            self.dock_items.paint(ctx, data, env);
+        // Animate the panes to their new targets
+        if layout_changed || old_data.maximized_pane != data.maximized_pane {
+            self.model.start_animating();
+            ctx.request_anim_frame();
+            ctx.request_layout();
         }
+    }
 
-        self.persistent_items.paint(ctx, data, env);
+    fn layout(&mut self, ctx: &mut druid::LayoutCtx, bc: &druid::BoxConstraints, data: &DockData, env: &druid::Env) -> druid::Size {
+        // Fill the space given, or where it's unbounded, be just big enough for the panes
+        let content_size = pane_layout::content_size(&data.panes);
+        let size = Size::new(
+            if bc.is_width_bounded() { bc.max().width } else { content_size.width },
+            if bc.is_height_bounded() { bc.max().height } else { content_size.height },
+        );
+        let size = bc.constrain(size);
+        trace!(target: LOG_LAYOUT, "Laying out {} panes in {:?}", data.panes.len(), size);
+        self.model.set_dock_size(size, self.persistent_items_rect);
+        self.model.snap_if_not_animating(data);
+
+        for (index, pane) in self.model.panes().iter().enumerate() {
+            let opacity = DockColors::from_env(env).pane_opacity(self.model.is_pane_idle(pane.pane_id));
+            let (pane_widgets, pane_data) = match (self.pane_widgets.get_mut(&pane.pane_id), data.panes.get(index)) {
+                (Some(pane_widgets), Some(pane_data)) => (pane_widgets, pane_data),
+                _ => continue,
+            };
+            let pane_env = pane_widgets.env(env, opacity);
+            let env = &pane_env;
+            let contents_rect = self.model.contents_rect(index);
+            let pane_bc = BoxConstraints::tight(contents_rect.size());
+            pane_widgets.contents.layout(ctx, &pane_bc, pane_data, env);
+            pane_widgets.contents.set_origin(ctx, contents_rect.origin());
+
+            let header_rect = self.model.header_rect(index);
+            let header_bc = BoxConstraints::tight(header_rect.size());
+            pane_widgets.header.layout(ctx, &header_bc, pane_data, env);
+            pane_widgets.header.set_origin(ctx, header_rect.origin());
+        };
 
-        let panes_iter = self.panes.iter_mut();
-        let data_iter = data.panes.iter();
-        for ((_pane_location_data,pane_widget,pane_header_widget), pane_data) in panes_iter.zip(data_iter) {
-            // Paint background
-            ctx.fill(pane_widget.layout_rect(), &env.get(crate::PANE_BACKGROUND_COLOR_KEY));
-            // Draw header background
-            ctx.fill(pane_header_widget.layout_rect(), &env.get(crate::PANE_HEADER_COLOR_KEY));
+        size
+    }
 
-            // Draw pane contents
-            pane_widget.paint(ctx, pane_data, env);
-            // Draw header
-            pane_header_widget.paint(ctx, pane_data, env);
+    fn paint(&mut self, ctx: &mut druid::PaintCtx, data: &DockData, env: &druid::Env) {
+        let colors = DockColors::from_env(env);
+        let mut canvas = WidgetCanvas { ctx, pane_widgets: &mut self.pane_widgets, env };
+        dock_painting::paint_panes(&mut canvas, &self.model, data, &colors);
 
-            // Draw border
-            let left = pane_widget.layout_rect().x0.min(pane_header_widget.layout_rect().x0);
-            let right = pane_widget.layout_rect().x1.min(pane_header_widget.layout_rect().x1);
-            let top = pane_header_widget.layout_rect().y0;
-            let bottom = pane_widget.layout_rect().y1;
-            let border_color = Color::rgba8(0, 0, 0, 100);
-            ctx.fill(Rect::new(left - 1.0, top, left, bottom), &border_color);
-            ctx.fill(Rect::new(right, top, right + 1.0, bottom), &border_color);
-        };
+        if self.show_debug_overlay {
+            self.paint_debug_overlay(ctx, data);
+        }
     }
-}
\ No newline at end of file
+}
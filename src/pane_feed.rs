@@ -0,0 +1,79 @@
+use druid::{ExtEventError, ExtEventSink, Selector, Target};
+use crate::AppState;
+
+/// A change to what a pane shows, sent from a background thread.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PaneUpdate {
+    SetText(String),
+    /// Adds a line to the end of the text
+    AppendLine(String),
+    SetTitle(Option<String>),
+    SetBadge(Option<u32>),
+}
+
+/// A pane update on its way to the app.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaneFeedUpdate {
+    pub pane_id: i32,
+    pub update: PaneUpdate,
+}
+
+/// Sent to the app by `PaneFeed`. The app delegate passes it to `apply_update`.
+pub const PANE_FEED_UPDATE: Selector<PaneFeedUpdate> = Selector::new("polysoft.druid-demo.pane-feed-update");
+
+/// Lets background threads update panes by their ID, through the app's `ExtEventSink`.
+/// Get one with `PaneFeed::new(launcher.get_external_handle())`, and clone it for each thread.
+#[derive(Clone)]
+pub struct PaneFeed {
+    sink: ExtEventSink,
+}
+
+impl PaneFeed {
+    pub fn new(sink: ExtEventSink) -> Self {
+        Self { sink }
+    }
+
+    /// Fails once the app has quit.
+    pub fn send(&self, pane_id: i32, update: PaneUpdate) -> Result<(), ExtEventError> {
+        self.sink.submit_command(PANE_FEED_UPDATE, PaneFeedUpdate { pane_id, update }, Target::Auto)
+    }
+
+    pub fn set_text(&self, pane_id: i32, text: impl Into<String>) -> Result<(), ExtEventError> {
+        self.send(pane_id, PaneUpdate::SetText(text.into()))
+    }
+
+    pub fn append_line(&self, pane_id: i32, line: impl Into<String>) -> Result<(), ExtEventError> {
+        self.send(pane_id, PaneUpdate::AppendLine(line.into()))
+    }
+
+    pub fn set_title(&self, pane_id: i32, title: Option<String>) -> Result<(), ExtEventError> {
+        self.send(pane_id, PaneUpdate::SetTitle(title))
+    }
+
+    pub fn set_badge(&self, pane_id: i32, badge: Option<u32>) -> Result<(), ExtEventError> {
+        self.send(pane_id, PaneUpdate::SetBadge(badge))
+    }
+}
+
+/// Applies the update to the pane with the ID, wherever it is.
+/// Only that pane changes, so the widgets of the others aren't updated.
+/// Returns false if there is no such pane, like if it was closed.
+pub fn apply_update(state: &mut AppState, feed_update: &PaneFeedUpdate) -> bool {
+    let (dock_index, pane_index) = match state.find_pane(feed_update.pane_id) {
+        Some(found) => found,
+        None => return false,
+    };
+    let pane = &mut state.docks[dock_index].panes[pane_index];
+    match &feed_update.update {
+        PaneUpdate::SetText(text) => pane.text = text.clone(),
+        PaneUpdate::AppendLine(line) => {
+            if !pane.text.is_empty() {
+                pane.text.push('\n');
+            }
+            pane.text.push_str(line);
+        }
+        PaneUpdate::SetTitle(title) => pane.title = title.clone(),
+        PaneUpdate::SetBadge(badge) => pane.badge = *badge,
+    }
+    true
+}
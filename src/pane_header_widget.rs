@@ -1,21 +1,35 @@
 use druid::widget::{Widget, Label, Padding, Container};
-use druid::{WidgetPod, Point, WidgetExt, Color, BoxConstraints, Size};
-use crate::PaneData;
+use druid::{WidgetPod, Point, WidgetExt, BoxConstraints, Size};
+use crate::{PaneData, PANE_HEADER_BUTTON_COLOR_KEY};
+use crate::pane_layout::HEADER_BUTTON_WIDTH;
 
 pub struct PaneHeaderWidget {
     title: WidgetPod<PaneData, Padding<PaneData, Label<PaneData>>>,
+    minimize_label: WidgetPod<PaneData, Container<PaneData>>,
+    maximize_label: WidgetPod<PaneData, Container<PaneData>>,
     close_label: WidgetPod<PaneData, Container<PaneData>>,
 }
 
-impl PaneHeaderWidget {
-    pub fn new() -> Self {
-        let title = Label::new(|data: &PaneData, _: &_| format!("Pane {} header", data.id)).padding(3.0);
-        let close_label = Label::new("✖").padding(3.0).expand_height().background(Color::rgba8(255, 255, 255, 20));
-        Self { title: WidgetPod::new(title), close_label: WidgetPod::new(close_label) }
+fn header_button(text: &str) -> Container<PaneData> {
+    // From the env, so that it fades with the pane
+    Label::new(text).center().background(PANE_HEADER_BUTTON_COLOR_KEY)
+}
+
+impl Default for PaneHeaderWidget {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    pub fn is_in_button(&self, point: Point) -> bool {
-        self.close_label.layout_rect().contains(point)
+impl PaneHeaderWidget {
+    pub fn new() -> Self {
+        let title = Label::new(|data: &PaneData, _: &_| data.header_text()).padding(3.0);
+        Self {
+            title: WidgetPod::new(title),
+            minimize_label: WidgetPod::new(header_button("—")),
+            maximize_label: WidgetPod::new(header_button("☐")),
+            close_label: WidgetPod::new(header_button("✖")),
+        }
     }
 }
 
@@ -23,32 +37,51 @@ impl PaneHeaderWidget {
 impl Widget<PaneData> for PaneHeaderWidget {
     fn event(&mut self, ctx: &mut druid::EventCtx, event: &druid::Event, data: &mut PaneData, env: &druid::Env) {
         self.title.event(ctx, event, data, env);
+        self.minimize_label.event(ctx, event, data, env);
+        self.maximize_label.event(ctx, event, data, env);
         self.close_label.event(ctx, event, data, env);
     }
 
     fn lifecycle(&mut self, ctx: &mut druid::LifeCycleCtx, event: &druid::LifeCycle, data: &PaneData, env: &druid::Env) {
         self.title.lifecycle(ctx, event, data, env);
+        self.minimize_label.lifecycle(ctx, event, data, env);
+        self.maximize_label.lifecycle(ctx, event, data, env);
         self.close_label.lifecycle(ctx, event, data, env);
     }
 
     fn update(&mut self, ctx: &mut druid::UpdateCtx, _old_data: &PaneData, data: &PaneData, env: &druid::Env) {
         self.title.update(ctx, data, env);
+        self.minimize_label.update(ctx, data, env);
+        self.maximize_label.update(ctx, data, env);
         self.close_label.update(ctx, data, env);
     }
 
     fn layout(&mut self, ctx: &mut druid::LayoutCtx, bc: &druid::BoxConstraints, data: &PaneData, env: &druid::Env) -> druid::Size {
         let child_bc = BoxConstraints::new(Size::new(0.0, 0.0), bc.max());
+        // The buttons have a fixed size, so that the dock can find them without the widgets
+        let button_size = Size::new(HEADER_BUTTON_WIDTH, bc.max().height);
+        let button_bc = BoxConstraints::tight(button_size);
 
         let _title_layout_result = self.title.layout(ctx, &child_bc, data, env);
-        let close_label_layout_result = self.close_label.layout(ctx, &child_bc, data, env);
+        self.minimize_label.layout(ctx, &button_bc, data, env);
+        self.maximize_label.layout(ctx, &button_bc, data, env);
+        self.close_label.layout(ctx, &button_bc, data, env);
 
+        // Buttons are aligned to the right, with close being the rightmost, matching pane_layout::header_button_at
+        let close_label_x = bc.max().width - HEADER_BUTTON_WIDTH;
+        let maximize_label_x = close_label_x - HEADER_BUTTON_WIDTH;
+        let minimize_label_x = maximize_label_x - HEADER_BUTTON_WIDTH;
         self.title.set_origin(ctx, Point::new(0.0, 0.0));
-        self.close_label.set_origin(ctx, Point::new(bc.max().width - close_label_layout_result.width, 0.0));
+        self.minimize_label.set_origin(ctx, Point::new(minimize_label_x, 0.0));
+        self.maximize_label.set_origin(ctx, Point::new(maximize_label_x, 0.0));
+        self.close_label.set_origin(ctx, Point::new(close_label_x, 0.0));
         bc.max()
     }
 
     fn paint(&mut self, ctx: &mut druid::PaintCtx, data: &PaneData, env: &druid::Env) {
         self.title.paint(ctx, data, env);
+        self.minimize_label.paint(ctx, data, env);
+        self.maximize_label.paint(ctx, data, env);
         self.close_label.paint(ctx, data, env);
     }
 }
\ No newline at end of file
@@ -0,0 +1,122 @@
+use druid::{im, Point, Size};
+use crate::{PaneData, HEADER_HEIGHT, PANE_SPACING};
+
+// The header buttons are square
+pub const HEADER_BUTTON_WIDTH: f64 = HEADER_HEIGHT;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HeaderButton {
+    Minimize,
+    Maximize,
+    Close,
+}
+
+/// Where a pane needs to go to fit into the dock.
+/// Positions are relative to the bound side. Default dist from right.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PaneTarget {
+    pub x_pos: f64,
+    pub width: f64,
+    pub height: f64,
+    // How far the pane is pushed down past the bottom of the dock, for minimizing and hiding
+    pub y_offset: f64,
+}
+
+/// Finds where each pane needs to go, in the same order as the panes.
+/// The first pane is against the bound side, and each following pane is next to the one before it.
+pub fn target_layout(panes: &im::Vector<PaneData>, maximized_pane: Option<i32>, maximized_size: Size) -> Vec<PaneTarget> {
+    // It may have been closed
+    let maximized_pane = maximized_pane.filter(|id| panes.iter().any(|pane| pane.id == *id));
+
+    let mut next_x_pos = PANE_SPACING;
+    let mut targets = Vec::with_capacity(panes.len());
+    for pane in panes.iter() {
+        let target = match maximized_pane {
+            Some(maximized_id) if maximized_id == pane.id => PaneTarget {
+                x_pos: PANE_SPACING,
+                width: maximized_size.width,
+                height: maximized_size.height,
+                y_offset: 0.0,
+            },
+            // Hidden behind the maximized pane, but kept in place so that it only needs to come back up
+            Some(_) => PaneTarget {
+                x_pos: next_x_pos,
+                width: pane.width,
+                height: pane.height,
+                y_offset: pane.height + HEADER_HEIGHT,
+            },
+            None => PaneTarget {
+                x_pos: next_x_pos,
+                width: pane.width,
+                height: pane.height,
+                // Only the header is in view when minimized
+                y_offset: if pane.is_minimized { pane.height } else { 0.0 },
+            },
+        };
+        targets.push(target);
+        next_x_pos += pane.width + PANE_SPACING;
+    }
+    targets
+}
+
+/// The smallest size that fits every pane side by side, with their headers.
+pub fn content_size(panes: &im::Vector<PaneData>) -> Size {
+    let width = panes.iter().map(|pane| pane.width + PANE_SPACING).sum::<f64>() + PANE_SPACING;
+    let height = panes.iter().map(|pane| pane.height).fold(0.0, f64::max) + HEADER_HEIGHT;
+    Size::new(width, height)
+}
+
+/// Finds the header button at a point relative to the header.
+/// The buttons are aligned to the right, with close being the rightmost.
+pub fn header_button_at(point: Point, header_width: f64) -> Option<HeaderButton> {
+    if !(0.0..header_width).contains(&point.x) || !(0.0..HEADER_HEIGHT).contains(&point.y) {
+        return None;
+    }
+    let buttons_from_right = [HeaderButton::Close, HeaderButton::Maximize, HeaderButton::Minimize];
+    let slot = ((header_width - point.x) / HEADER_BUTTON_WIDTH) as usize;
+    buttons_from_right.get(slot).copied()
+}
+
+// For finding where to place a pane when moving it
+// It does this based on target locations, not actual locations
+// The exclude param is to not check the pane you're repositioning
+// It does it based on the center of the panes
+pub fn get_closest_pane_to_right(targets: &[PaneTarget], x_pos: f64, exclude: usize) -> Option<usize> {
+    let mut closest_position = 0.0;
+    let mut closest_position_pane_index: Option<usize> = None;
+    for (i, target) in targets.iter().enumerate() {
+        let pane_location_center = target.x_pos + target.width / 2.0;
+        if i != exclude && pane_location_center > closest_position && pane_location_center < x_pos {
+            closest_position = pane_location_center;
+            closest_position_pane_index = Some(i);
+        }
+    }
+    closest_position_pane_index
+}
+
+/// Finds the index a moved pane needs to go to in the data, based on its position.
+pub fn get_new_index(targets: &[PaneTarget], x_pos: f64, moved_index: usize) -> usize {
+    match get_closest_pane_to_right(targets, x_pos, moved_index) {
+        // It goes to the left of the closest pane. Panes to the right of the moved pane don't shift.
+        Some(closest) if closest < moved_index => closest + 1,
+        Some(closest) => closest,
+        None => 0,
+    }
+}
+
+/// Finds the index a pane needs to be inserted at to be dropped at a position.
+/// It goes after every pane whose center is closer to the bound side.
+pub fn get_insert_index(targets: &[PaneTarget], x_pos: f64) -> usize {
+    targets.iter().filter(|target| target.x_pos + target.width / 2.0 < x_pos).count()
+}
+
+/// True if the panes were added, removed, reordered, resized, or minimized.
+/// Changes to the contents of the panes don't count.
+pub fn layout_changed(old_panes: &im::Vector<PaneData>, new_panes: &im::Vector<PaneData>) -> bool {
+    old_panes.len() != new_panes.len() || old_panes.iter().zip(new_panes.iter()).any(|(old_pane, new_pane)| {
+        old_pane.id != new_pane.id
+            || old_pane.width != new_pane.width
+            || old_pane.height != new_pane.height
+            || old_pane.is_minimized != new_pane.is_minimized
+    })
+}
@@ -1,27 +1,99 @@
-use druid::widget::{Widget, Flex, Label, SizedBox};
-use druid::{WidgetPod, WidgetExt, Point};
+use druid::widget::{Widget, Flex, Label, LineBreaking, Padding, Scroll};
+use druid::{WidgetPod, WidgetExt, Point, Axis};
 use crate::PaneData;
 
+// How close to the bottom the contents have to be scrolled to stick to it
+const STICKY_BOTTOM_DISTANCE: f64 = 4.0;
+
+/// Where the contents of a pane are scrolled to, kept apart from the scroll container,
+/// which clamps its offset to the contents each time they're laid out. That loses the offset
+/// whenever the contents are briefly shorter, like after their text is replaced,
+/// or while the pane is in a wider dock and the text wraps onto fewer lines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollPosition {
+    pub offset: f64,
+    /// Stays at the bottom as content is added, like a chat or a log
+    pub sticks_to_bottom: bool,
+}
+
+impl Default for ScrollPosition {
+    fn default() -> Self {
+        Self { offset: 0.0, sticks_to_bottom: true }
+    }
+}
+
+impl ScrollPosition {
+    /// Records where the user scrolled to.
+    pub fn scrolled_to(offset: f64, content_height: f64, viewport_height: f64) -> Self {
+        let bottom_offset = (content_height - viewport_height).max(0.0);
+        Self { offset, sticks_to_bottom: offset >= bottom_offset - STICKY_BOTTOM_DISTANCE }
+    }
+
+    /// Where the contents should be scrolled to, now that they're the height.
+    pub fn offset_for(&self, content_height: f64, viewport_height: f64) -> f64 {
+        let bottom_offset = (content_height - viewport_height).max(0.0);
+        if self.sticks_to_bottom { bottom_offset } else { self.offset.min(bottom_offset) }
+    }
+}
+
 pub struct PaneWidget {
-    contents: WidgetPod<PaneData, SizedBox<PaneData>>,
+    contents: WidgetPod<PaneData, Scroll<PaneData, Padding<PaneData, Flex<PaneData>>>>,
+    // Kept with the widget, which stays with the pane when it's moved, minimized, or dragged to another dock
+    scroll_position: ScrollPosition,
+}
+
+impl Default for PaneWidget {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PaneWidget {
     pub fn new() -> Self {
-        let placeholder_label = Label::new(|data: &PaneData, _: &_| format!("Pane {} content", data.id));
-        Self { contents: WidgetPod::new(
+        let text_label = Label::new(|data: &PaneData, _: &_| data.content_text())
+            .with_line_break_mode(LineBreaking::WordWrap);
+        Self {
+            contents: WidgetPod::new(
                 Flex::column()
-                    .with_child(placeholder_label)
-                    .expand()
-            )
+                    .with_child(text_label)
+                    .padding(4.0)
+                    .scroll()
+                    .vertical()
+                    .content_must_fill(true)
+            ),
+            scroll_position: ScrollPosition::default(),
+        }
+    }
+
+    /// Where the contents are kept scrolled to, whatever the scroll container was last laid out at.
+    pub fn scroll_position(&self) -> ScrollPosition {
+        self.scroll_position
+    }
+
+    /// Records where the user scrolled the contents to, if the offset of the scroll container changed.
+    /// Only scrolling by the user moves it, not the layout clamping it while the contents are short.
+    pub fn contents_scrolled(&mut self, offset_before: f64, offset: f64, content_height: f64, viewport_height: f64) {
+        if offset != offset_before {
+            self.scroll_position = ScrollPosition::scrolled_to(offset, content_height, viewport_height);
         }
     }
+
+    fn content_height(&self) -> f64 {
+        self.contents.widget().child_size().height
+    }
+
+    fn viewport_height(&self) -> f64 {
+        self.contents.widget().viewport_rect().height()
+    }
 }
 
 
 impl Widget<PaneData> for PaneWidget {
     fn event(&mut self, ctx: &mut druid::EventCtx, event: &druid::Event, data: &mut PaneData, env: &druid::Env) {
+        let offset_before = self.contents.widget().offset().y;
         self.contents.event(ctx, event, data, env);
+        let offset = self.contents.widget().offset().y;
+        self.contents_scrolled(offset_before, offset, self.content_height(), self.viewport_height());
     }
 
     fn lifecycle(&mut self, ctx: &mut druid::LifeCycleCtx, event: &druid::LifeCycle, data: &PaneData, env: &druid::Env) {
@@ -35,10 +107,12 @@ impl Widget<PaneData> for PaneWidget {
     fn layout(&mut self, ctx: &mut druid::LayoutCtx, bc: &druid::BoxConstraints, data: &PaneData, env: &druid::Env) -> druid::Size {
         let layout_result = self.contents.layout(ctx, bc, data, env);
         self.contents.set_origin(ctx, Point::new(0.0, 0.0));
+        let offset = self.scroll_position.offset_for(self.content_height(), self.viewport_height());
+        self.contents.widget_mut().scroll_to_on_axis(ctx, Axis::Vertical, offset);
         layout_result
     }
 
     fn paint(&mut self, ctx: &mut druid::PaintCtx, data: &PaneData, env: &druid::Env) {
         self.contents.paint(ctx, data, env);
     }
-}
\ No newline at end of file
+}
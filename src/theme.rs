@@ -0,0 +1,57 @@
+use std::io;
+use std::path::Path;
+use druid::{Color, Data};
+use serde::Deserialize;
+use crate::{DOCK_BACKGROUND_COLOR, PANE_BACKGROUND_COLOR, PANE_HEADER_COLOR, PANE_SCROLLBAR_COLOR};
+
+/// The colors of the dock. The opacity of the dock background comes from the `Appearance` instead.
+#[derive(Clone, Debug, Data)]
+pub struct Theme {
+    pub dock_background: Color,
+    pub pane_background: Color,
+    pub pane_header: Color,
+    /// The scrollbars of the pane contents
+    pub pane_scrollbar: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            dock_background: DOCK_BACKGROUND_COLOR,
+            pane_background: PANE_BACKGROUND_COLOR,
+            pane_header: PANE_HEADER_COLOR,
+            pane_scrollbar: PANE_SCROLLBAR_COLOR,
+        }
+    }
+}
+
+// Colors are hex strings, like "#464646" or "#000000c8". Any that are left out keep their default.
+#[derive(Deserialize)]
+struct ThemeFile {
+    dock_background: Option<String>,
+    pane_background: Option<String>,
+    pane_header: Option<String>,
+    pane_scrollbar: Option<String>,
+}
+
+impl Theme {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        let file: ThemeFile = serde_json::from_str(&json)?;
+        let defaults = Self::default();
+        Ok(Self {
+            dock_background: parse_color(file.dock_background, defaults.dock_background)?,
+            pane_background: parse_color(file.pane_background, defaults.pane_background)?,
+            pane_header: parse_color(file.pane_header, defaults.pane_header)?,
+            pane_scrollbar: parse_color(file.pane_scrollbar, defaults.pane_scrollbar)?,
+        })
+    }
+}
+
+fn parse_color(hex: Option<String>, default: Color) -> io::Result<Color> {
+    match hex {
+        Some(hex) => Color::from_hex_str(&hex)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {:?}", hex, err))),
+        None => Ok(default),
+    }
+}
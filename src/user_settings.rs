@@ -0,0 +1,127 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use druid::{Point, Rect, Size};
+use serde::{Deserialize, Serialize};
+use crate::{AppState, Appearance};
+
+/// Where the settings are within the config directory.
+pub const SETTINGS_FILE: &str = "druid-pane-demo/dock-settings.json";
+
+/// Where the settings are kept, in `$XDG_CONFIG_HOME`, or `~/.config` without it,
+/// so that they're the same wherever the dock is launched from.
+/// None if neither is known, and then they aren't kept.
+pub fn default_settings_path() -> Option<PathBuf> {
+    settings_path_in(std::env::var_os("XDG_CONFIG_HOME"), std::env::var_os("HOME"))
+}
+
+/// Where the settings are for the values of `$XDG_CONFIG_HOME` and `$HOME`.
+/// A config home that isn't an absolute path is ignored, like the XDG spec says.
+pub fn settings_path_in(config_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    let config_home = config_home.map(PathBuf::from).filter(|config_home| config_home.is_absolute())
+        .or_else(|| home.filter(|home| !home.is_empty()).map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join(SETTINGS_FILE))
+}
+
+/// The parts of the app state that are kept between runs.
+/// Settings missing from the file keep their defaults, so older files still load.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettings {
+    pub appearance: Appearance,
+    /// Where the main window was on the screen, which isn't known until it has been opened once
+    pub main_window: Option<WindowGeometry>,
+    pub always_on_top: bool,
+    pub show_dock: bool,
+}
+
+/// A window's position and size on the screen, in display points.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl WindowGeometry {
+    pub fn from_rect(rect: Rect) -> Self {
+        Self {
+            x: rect.x0,
+            y: rect.y0,
+            width: rect.width(),
+            height: rect.height(),
+        }
+    }
+
+    pub fn to_rect(self) -> Rect {
+        Rect::from_origin_size(Point::new(self.x, self.y), Size::new(self.width, self.height))
+    }
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self {
+            appearance: Appearance::default(),
+            main_window: None,
+            always_on_top: false,
+            show_dock: true,
+        }
+    }
+}
+
+impl UserSettings {
+    pub fn from_state(state: &AppState) -> Self {
+        Self {
+            appearance: state.appearance,
+            main_window: state.main_window_rect.map(WindowGeometry::from_rect),
+            always_on_top: state.always_on_top,
+            show_dock: state.show_dock,
+        }
+    }
+
+    pub fn apply(&self, state: &mut AppState) {
+        state.appearance = self.appearance;
+        state.main_window_rect = self.main_window.map(WindowGeometry::to_rect);
+        state.always_on_top = self.always_on_top;
+        state.show_dock = self.show_dock;
+    }
+
+    /// Creates the directory it goes in if there isn't one yet.
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+/// Moves a window onto the screen, for when the monitors changed since it was saved.
+/// It goes on the work area it overlaps the most, or the first one if it's on none of them,
+/// and shrinks if it doesn't fit. Returns None if there are no work areas.
+pub fn clamp_to_work_areas(window_rect: Rect, work_areas: &[Rect]) -> Option<Rect> {
+    let overlap = |work_area: &Rect| {
+        let intersection = work_area.intersect(window_rect);
+        intersection.width() * intersection.height()
+    };
+    let work_area = work_areas.iter()
+        .filter(|work_area| overlap(work_area) > 0.0)
+        .max_by(|a, b| overlap(a).total_cmp(&overlap(b)))
+        .or_else(|| work_areas.first())?;
+
+    let size = Size::new(
+        window_rect.width().min(work_area.width()),
+        window_rect.height().min(work_area.height()),
+    );
+    let origin = Point::new(
+        window_rect.x0.clamp(work_area.x0, work_area.x1 - size.width),
+        window_rect.y0.clamp(work_area.y0, work_area.y1 - size.height),
+    );
+    Some(Rect::from_origin_size(origin, size))
+}
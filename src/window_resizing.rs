@@ -0,0 +1,78 @@
+use druid::{Point, Rect, Size, Vec2};
+
+/// How far into the window from its edges the resize grips reach.
+pub const GRIP_SIZE: f64 = 6.0;
+// The corners reach further along the edges, so they're easier to hit
+const CORNER_GRIP_SIZE: f64 = 16.0;
+
+/// Which edges of the window a resize grip moves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResizeGrip {
+    pub left: bool,
+    pub right: bool,
+    pub top: bool,
+    pub bottom: bool,
+}
+
+impl ResizeGrip {
+    pub fn is_corner(&self) -> bool {
+        (self.left || self.right) && (self.top || self.bottom)
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.left || self.right
+    }
+}
+
+/// The grip at the point in the window, if any.
+pub fn grip_at(pos: Point, window_size: Size) -> Option<ResizeGrip> {
+    let near = |distance: f64, reach: f64| distance >= 0.0 && distance < reach;
+    let on_edge = ResizeGrip {
+        left: near(pos.x, GRIP_SIZE),
+        right: near(window_size.width - pos.x, GRIP_SIZE),
+        top: near(pos.y, GRIP_SIZE),
+        bottom: near(window_size.height - pos.y, GRIP_SIZE),
+    };
+    if !(on_edge.left || on_edge.right || on_edge.top || on_edge.bottom) {
+        return None;
+    }
+    // Near a corner along an edge, the grip moves both edges
+    let on_top_or_bottom = on_edge.top || on_edge.bottom;
+    let on_left_or_right = on_edge.left || on_edge.right;
+    Some(ResizeGrip {
+        left: on_edge.left || (on_top_or_bottom && near(pos.x, CORNER_GRIP_SIZE)),
+        right: on_edge.right || (on_top_or_bottom && near(window_size.width - pos.x, CORNER_GRIP_SIZE)),
+        top: on_edge.top || (on_left_or_right && near(pos.y, CORNER_GRIP_SIZE)),
+        bottom: on_edge.bottom || (on_left_or_right && near(window_size.height - pos.y, CORNER_GRIP_SIZE)),
+    })
+}
+
+/// The strips along the window's edges that the grips take up, for the input region.
+pub fn grip_rects(window_size: Size) -> [Rect; 4] {
+    let (width, height) = (window_size.width, window_size.height);
+    [
+        Rect::new(0.0, 0.0, GRIP_SIZE, height),
+        Rect::new(width - GRIP_SIZE, 0.0, width, height),
+        Rect::new(0.0, 0.0, width, GRIP_SIZE),
+        Rect::new(0.0, height - GRIP_SIZE, width, height),
+    ]
+}
+
+/// Where the window goes when the grip is dragged by the pointer movement, in screen coordinates.
+/// The edges the grip doesn't move stay where they are, even when the window can't get any smaller.
+pub fn resize(window_rect: Rect, grip: ResizeGrip, pointer_change: Vec2, min_size: Size) -> Rect {
+    let mut resized = window_rect;
+    if grip.left {
+        resized.x0 = (window_rect.x0 + pointer_change.x).min(window_rect.x1 - min_size.width);
+    }
+    if grip.right {
+        resized.x1 = (window_rect.x1 + pointer_change.x).max(window_rect.x0 + min_size.width);
+    }
+    if grip.top {
+        resized.y0 = (window_rect.y0 + pointer_change.y).min(window_rect.y1 - min_size.height);
+    }
+    if grip.bottom {
+        resized.y1 = (window_rect.y1 + pointer_change.y).max(window_rect.y0 + min_size.height);
+    }
+    resized
+}
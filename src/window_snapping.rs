@@ -0,0 +1,45 @@
+use druid::{Point, Rect, Size};
+
+/// How close a window edge has to be to a screen edge to snap to it, in display points.
+pub const SNAP_DISTANCE: f64 = 16.0;
+
+/// A monitor's bounds and its work area, which leaves out things like the taskbar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SnapArea {
+    pub bounds: Rect,
+    pub work_area: Rect,
+}
+
+/// Moves the window position so that the window's edges line up with the nearest edges of a monitor
+/// or its work area, if they're within the snap distance. Each axis snaps on its own,
+/// so a window near the bottom right of the work area snaps into the corner.
+pub fn snap_window_position(pos: Point, window_size: Size, areas: &[SnapArea]) -> Point {
+    let mut x_edges = vec![];
+    let mut y_edges = vec![];
+    for area in areas {
+        for rect in [area.bounds, area.work_area] {
+            x_edges.extend([rect.x0, rect.x1]);
+            y_edges.extend([rect.y0, rect.y1]);
+        }
+    }
+    Point::new(
+        snap_axis(pos.x, window_size.width, &x_edges),
+        snap_axis(pos.y, window_size.height, &y_edges),
+    )
+}
+
+// Snaps either side of the window, whichever is closest to an edge
+fn snap_axis(start: f64, length: f64, edges: &[f64]) -> f64 {
+    let mut snapped = start;
+    let mut closest = SNAP_DISTANCE;
+    for edge in edges {
+        for offset in [0.0, length] {
+            let distance = (edge - (start + offset)).abs();
+            if distance <= closest {
+                closest = distance;
+                snapped = edge - offset;
+            }
+        }
+    }
+    snapped
+}
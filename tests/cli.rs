@@ -0,0 +1,46 @@
+use clap::Parser;
+use druid::{Point, Size};
+use druid_pane_demo::{DockData, PaneData, HEADER_HEIGHT, PANE_SPACING};
+use druid_pane_demo::cli::{dump_layout, parse_geometry, Cli, WindowGeometry};
+
+#[test]
+fn geometry_can_have_a_position() {
+    assert_eq!(parse_geometry("1024x600"), Ok(WindowGeometry { size: Size::new(1024.0, 600.0), position: None }));
+    assert_eq!(parse_geometry("800x500+100+50"),
+        Ok(WindowGeometry { size: Size::new(800.0, 500.0), position: Some(Point::new(100.0, 50.0)) }));
+    assert!(parse_geometry("800").is_err());
+    assert!(parse_geometry("800x500+100").is_err());
+}
+
+#[test]
+fn the_options_are_parsed() {
+    let cli = Cli::try_parse_from(["druid-pane-demo", "--panes", "3", "--hidden", "--geometry", "800x500"]).unwrap();
+    assert_eq!(cli.panes, Some(3));
+    assert!(cli.hidden);
+    assert_eq!(cli.window_size(), Some(Size::new(800.0, 500.0)));
+    assert!(Cli::try_parse_from(["druid-pane-demo", "--hidden", "--shown"]).is_err());
+    assert!(Cli::try_parse_from(["druid-pane-demo", "--panes=-1"]).is_err());
+    assert_eq!(Cli::try_parse_from(["druid-pane-demo", "--panes", "0"]).unwrap().panes, Some(0));
+}
+
+#[test]
+fn a_running_dock_ignores_the_startup_options() {
+    let cli = Cli::try_parse_from(["druid-pane-demo", "--panes", "3", "--geometry", "800x500", "--open", "Notes"]).unwrap();
+    assert_eq!(cli.options_ignored_when_running(), vec!["--panes", "--geometry"]);
+    let cli = Cli::try_parse_from(["druid-pane-demo", "--theme", "theme.json", "--always-on-top"]).unwrap();
+    assert!(cli.options_ignored_when_running().is_empty());
+}
+
+#[test]
+fn the_dumped_layout_has_the_panes_from_the_right() {
+    let dock = DockData::new((0..2).map(PaneData::new).collect());
+    let window_size = Size::new(1024.0, 600.0);
+    let pane_rects = dump_layout(&dock, window_size);
+    assert_eq!(pane_rects.len(), 2);
+    assert_eq!(pane_rects[0].id, 0);
+    // Next to each other at the bottom, with the first against the right
+    assert_eq!(pane_rects[0].contents[3], window_size.height);
+    assert_eq!(pane_rects[0].header[3], pane_rects[0].contents[1]);
+    assert_eq!(pane_rects[0].header[3] - pane_rects[0].header[1], HEADER_HEIGHT);
+    assert_eq!(pane_rects[1].contents[2] + PANE_SPACING, pane_rects[0].contents[0]);
+}
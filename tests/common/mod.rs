@@ -0,0 +1,58 @@
+// Shared by the test files and the benchmarks, which each use only some of it
+#![allow(dead_code)]
+
+use druid::{im, Point, Rect, Size};
+use druid_pane_demo::{AppState, DockData, PaneData};
+use druid_pane_demo::dock_model::DockModel;
+
+pub const DOCK_SIZE: Size = Size::new(1024.0, 600.0);
+pub const PERSISTENT_ITEMS_RECT: Rect = Rect::new(900.0, 0.0, 1024.0, 60.0);
+// 60 FPS, in nanoseconds like AnimFrame
+pub const FRAME_INTERVAL: u64 = 16_000_000;
+
+pub fn dock_with_panes(count: i32) -> (DockModel, DockData) {
+    let data = DockData::new((0..count).map(PaneData::new).collect());
+    let mut model = DockModel::new();
+    model.set_dock_size(DOCK_SIZE, PERSISTENT_ITEMS_RECT);
+    model.sync(&data);
+    (model, data)
+}
+
+// The whole app, with one dock
+pub fn state_with_panes(count: i32) -> AppState {
+    AppState::new((0..count).map(PaneData::new).collect::<im::Vector<_>>())
+}
+
+pub fn pane_ids(data: &DockData) -> Vec<i32> {
+    data.panes.iter().map(|pane| pane.id).collect()
+}
+
+// Runs animation frames until every pane is at its target
+pub fn settle(model: &mut DockModel, data: &DockData) {
+    model.start_animating();
+    for _ in 0..500 {
+        model.animate(FRAME_INTERVAL, data);
+        if !model.is_animating() {
+            return;
+        }
+    }
+    panic!("The panes did not settle");
+}
+
+// A point on the header title, away from the buttons
+pub fn header_title_point(model: &DockModel, index: usize) -> Point {
+    let header_rect = model.header_rect(index);
+    Point::new(header_rect.x0 + 20.0, header_rect.center().y)
+}
+
+// Presses on the point and moves the pointer horizontally without releasing it.
+// Returns where the pointer ended up.
+pub fn drag(model: &mut DockModel, data: &mut DockData, from: Point, distance: f64, steps: usize) -> Point {
+    assert!(model.pointer_down(from, 1, data));
+    let mut pos = from;
+    for _ in 0..steps {
+        pos.x += distance / steps as f64;
+        assert_eq!(model.pointer_move(pos, data), None);
+    }
+    pos
+}
@@ -0,0 +1,316 @@
+use druid::{Point, Rect, Vec2};
+use druid_pane_demo::{AutoHide, DockData};
+use druid_pane_demo::dock_model::{DockModel, DropPreview, AUTO_HIDE_STRIP_HEIGHT};
+use druid_pane_demo::drag_prediction::DragSettings;
+
+mod common;
+use common::{DOCK_SIZE, FRAME_INTERVAL, dock_with_panes, drag, header_title_point, pane_ids, settle};
+
+fn close_button_point(model: &DockModel, index: usize) -> Point {
+    let header_rect = model.header_rect(index);
+    Point::new(header_rect.x1 - 5.0, header_rect.center().y)
+}
+
+fn maximize_button_point(model: &DockModel, index: usize) -> Point {
+    let header_rect = model.header_rect(index);
+    Point::new(header_rect.x1 - 30.0, header_rect.center().y)
+}
+
+// Runs enough animation frames to hold the pointer down for longer than the hold duration
+fn hold(model: &mut DockModel, data: &DockData) {
+    let frames = model.drag_settings().hold_duration.as_nanos() as u64 / FRAME_INTERVAL + 1;
+    for _ in 0..frames {
+        model.animate(FRAME_INTERVAL, data);
+    }
+}
+
+// Pane contents for a pane at the given index with the default size, once it has settled
+fn expected_contents_rect(index: usize) -> Rect {
+    let x1 = DOCK_SIZE.width - 8.0 - index as f64 * 308.0 + 1.0;
+    Rect::new(x1 - 300.0, DOCK_SIZE.height - 480.0, x1, DOCK_SIZE.height)
+}
+
+#[test]
+fn dragging_a_header_left_moves_the_pane_away_from_the_bound_side() {
+    let (mut model, mut data) = dock_with_panes(3);
+    let start = header_title_point(&model, 0);
+    let end = drag(&mut model, &mut data, start, -350.0, 35);
+    assert_eq!(pane_ids(&data), vec![1, 0, 2]);
+
+    let drag_start_panes = model.pointer_up(end, &mut data).expect("the drag is recorded");
+    assert_eq!(drag_start_panes.iter().map(|pane| pane.id).collect::<Vec<_>>(), vec![0, 1, 2]);
+
+    settle(&mut model, &data);
+    for index in 0..3 {
+        let contents_rect = expected_contents_rect(index);
+        assert_eq!(model.contents_rect(index), contents_rect);
+        assert_eq!(model.header_rect(index), Rect::new(contents_rect.x0, contents_rect.y0 - 25.0, contents_rect.x1, contents_rect.y0));
+    }
+}
+
+#[test]
+fn dragging_a_header_right_moves_the_pane_to_the_bound_side() {
+    let (mut model, mut data) = dock_with_panes(3);
+    let start = header_title_point(&model, 2);
+    let end = drag(&mut model, &mut data, start, 620.0, 62);
+    assert_eq!(pane_ids(&data), vec![2, 0, 1]);
+    assert!(model.pointer_up(end, &mut data).is_some());
+
+    settle(&mut model, &data);
+    assert_eq!(model.panes().iter().map(|pane| pane.pane_id).collect::<Vec<_>>(), vec![2, 0, 1]);
+    assert_eq!(model.contents_rect(0), expected_contents_rect(0));
+}
+
+#[test]
+fn the_drag_stays_with_its_pane_when_panes_change_from_outside() {
+    let (mut model, mut data) = dock_with_panes(3);
+    let start = header_title_point(&model, 1);
+    let end = drag(&mut model, &mut data, start, -20.0, 2);
+    // Like a pane closed over IPC while the drag goes on
+    data.close_pane(0);
+    model.sync(&data);
+    assert_eq!(model.dragging_pane(), Some(0));
+
+    assert_eq!(model.pointer_move(Point::new(end.x - 10.0, end.y), &mut data), None);
+    assert_eq!(data.panes[model.dragging_pane().unwrap()].id, 1);
+}
+
+#[test]
+fn closing_the_dragged_pane_from_outside_drops_the_drag() {
+    let (mut model, mut data) = dock_with_panes(3);
+    let start = header_title_point(&model, 1);
+    let end = drag(&mut model, &mut data, start, -20.0, 2);
+    data.close_pane(1);
+    model.sync(&data);
+    assert_eq!(model.dragging_pane(), None);
+
+    // The pointer doesn't move another pane, or the window
+    assert_eq!(model.pointer_move(Point::new(end.x - 200.0, end.y), &mut data), None);
+    assert!(model.pointer_up(end, &mut data).is_none());
+    assert_eq!(pane_ids(&data), vec![0, 2]);
+}
+
+#[test]
+fn a_short_drag_does_not_reorder() {
+    let (mut model, mut data) = dock_with_panes(3);
+    let start = header_title_point(&model, 0);
+    let end = drag(&mut model, &mut data, start, -40.0, 4);
+    assert!(model.pointer_up(end, &mut data).is_none());
+    assert_eq!(pane_ids(&data), vec![0, 1, 2]);
+
+    settle(&mut model, &data);
+    assert_eq!(model.contents_rect(0), expected_contents_rect(0));
+}
+
+#[test]
+fn clicking_close_removes_exactly_that_pane() {
+    let (mut model, mut data) = dock_with_panes(3);
+    let pos = close_button_point(&model, 1);
+    assert!(model.pointer_down(pos, 1, &mut data));
+    assert!(model.pointer_up(pos, &mut data).is_none());
+    assert_eq!(pane_ids(&data), vec![0, 2]);
+
+    let changes = model.sync(&data);
+    assert!(changes.added.is_empty());
+    assert_eq!(changes.removed, vec![1]);
+
+    // The pane to the left of it slides over
+    settle(&mut model, &data);
+    assert_eq!(model.contents_rect(1), expected_contents_rect(1));
+}
+
+#[test]
+fn clicking_the_title_does_not_close() {
+    let (mut model, mut data) = dock_with_panes(2);
+    let pos = header_title_point(&model, 1);
+    assert!(model.pointer_down(pos, 1, &mut data));
+    assert!(model.pointer_up(pos, &mut data).is_none());
+    assert_eq!(pane_ids(&data), vec![0, 1]);
+}
+
+#[test]
+fn dragging_outside_the_panes_moves_the_window() {
+    let (mut model, mut data) = dock_with_panes(1);
+    let start = Point::new(10.0, 10.0);
+    assert!(model.pointer_down(start, 1, &mut data));
+    let window_change = model.pointer_move(Point::new(30.0, 5.0), &mut data);
+    assert_eq!(window_change.map(|change| (change.x, change.y)), Some((20.0, -5.0)));
+    assert!(model.pointer_up(Point::new(30.0, 5.0), &mut data).is_none());
+    assert_eq!(pane_ids(&data), vec![0]);
+}
+
+#[test]
+fn double_clicking_a_header_maximizes_the_pane() {
+    let (mut model, mut data) = dock_with_panes(2);
+    let pos = header_title_point(&model, 1);
+    assert!(!model.pointer_down(pos, 2, &mut data));
+    assert_eq!(data.maximized_pane, Some(1));
+
+    settle(&mut model, &data);
+    let maximized_size = model.maximized_size();
+    assert_eq!(model.contents_rect(1).size(), maximized_size);
+    assert_eq!(model.contents_rect(1).x1, DOCK_SIZE.width - 8.0 + 1.0);
+    // The other pane is hidden below the dock
+    assert!(model.header_rect(0).y0 >= DOCK_SIZE.height);
+}
+
+#[test]
+fn a_shaky_click_on_close_still_closes() {
+    let (mut model, mut data) = dock_with_panes(3);
+    let pos = close_button_point(&model, 1);
+    assert!(model.pointer_down(pos, 1, &mut data));
+    // Within the drag threshold
+    let shaken_pos = pos + Vec2::new(-2.0, 2.0);
+    assert_eq!(model.pointer_move(shaken_pos, &mut data), None);
+    assert!(model.pointer_up(shaken_pos, &mut data).is_none());
+    assert_eq!(pane_ids(&data), vec![0, 2]);
+}
+
+#[test]
+fn moving_past_the_threshold_vertically_cancels_the_click() {
+    let (mut model, mut data) = dock_with_panes(3);
+    let pos = close_button_point(&model, 1);
+    assert!(model.pointer_down(pos, 1, &mut data));
+    let moved_pos = pos + Vec2::new(0.0, 6.0);
+    model.pointer_move(moved_pos, &mut data);
+    assert!(model.pointer_up(moved_pos, &mut data).is_none());
+    assert_eq!(pane_ids(&data), vec![0, 1, 2]);
+}
+
+#[test]
+fn releasing_on_a_different_button_does_not_click() {
+    let (mut model, mut data) = dock_with_panes(2);
+    // So that the pointer can reach the next button without starting a drag
+    model.set_drag_settings(DragSettings { drag_threshold: 50.0, ..DragSettings::default() });
+    let pos = close_button_point(&model, 1);
+    assert!(model.pointer_down(pos, 1, &mut data));
+    let maximize_pos = maximize_button_point(&model, 1);
+    assert_eq!(model.pointer_move(maximize_pos, &mut data), None);
+    assert!(model.pointer_up(maximize_pos, &mut data).is_none());
+    assert_eq!(pane_ids(&data), vec![0, 1]);
+    assert_eq!(data.maximized_pane, None);
+}
+
+#[test]
+fn every_header_button_clicks_the_same_way() {
+    let (mut model, mut data) = dock_with_panes(2);
+    let header_rect = model.header_rect(1);
+    let minimize_pos = Point::new(header_rect.x1 - 55.0, header_rect.center().y);
+    assert!(model.pointer_down(minimize_pos, 1, &mut data));
+    assert!(model.pointer_up(minimize_pos + Vec2::new(1.0, -1.0), &mut data).is_none());
+    assert!(data.panes[1].is_minimized);
+
+    settle(&mut model, &data);
+    let maximize_pos = maximize_button_point(&model, 1);
+    assert!(model.pointer_down(maximize_pos, 1, &mut data));
+    assert!(model.pointer_up(maximize_pos + Vec2::new(1.0, -1.0), &mut data).is_none());
+    assert_eq!(data.maximized_pane, Some(1));
+}
+
+#[test]
+fn holding_a_button_down_does_not_click() {
+    let (mut model, mut data) = dock_with_panes(2);
+    let pos = close_button_point(&model, 1);
+    assert!(model.pointer_down(pos, 1, &mut data));
+    hold(&mut model, &data);
+    assert!(model.pointer_up(pos, &mut data).is_none());
+    assert_eq!(pane_ids(&data), vec![0, 1]);
+}
+
+#[test]
+fn holding_a_header_down_starts_the_drag_without_the_threshold() {
+    let (mut model, mut data) = dock_with_panes(2);
+    let pos = header_title_point(&model, 0);
+    assert!(model.pointer_down(pos, 1, &mut data));
+    model.pointer_move(pos + Vec2::new(-2.0, 0.0), &mut data);
+    // Not dragging yet
+    assert_eq!(model.panes()[0].actual_x_pos, 8.0);
+
+    hold(&mut model, &data);
+    model.pointer_move(pos + Vec2::new(-4.0, 0.0), &mut data);
+    assert_eq!(model.panes()[0].actual_x_pos, 12.0);
+}
+
+#[test]
+fn a_pane_from_another_dock_goes_where_it_is_dropped() {
+    let (model, data) = dock_with_panes(2);
+    let y = DOCK_SIZE.height - 100.0;
+    assert_eq!(model.drop_index_at(Point::new(DOCK_SIZE.width - 2.0, y), &data), 0);
+    // Past the center of the first pane
+    assert_eq!(model.drop_index_at(header_title_point(&model, 0), &data), 1);
+    assert_eq!(model.drop_index_at(Point::new(10.0, y), &data), 2);
+}
+
+#[test]
+fn the_drop_preview_makes_room_for_the_pane() {
+    let (mut model, data) = dock_with_panes(2);
+    let drop_preview = DropPreview { index: 1, width: 300.0, height: 480.0 };
+    assert!(model.set_drop_preview(Some(drop_preview)));
+    assert!(!model.set_drop_preview(Some(drop_preview)));
+    settle(&mut model, &data);
+    assert_eq!(model.contents_rect(0), expected_contents_rect(0));
+    assert_eq!(model.contents_rect(1), expected_contents_rect(2));
+    assert_eq!(model.drop_preview_rect(&data).map(|rect| rect.x1), Some(expected_contents_rect(1).x1));
+
+    model.set_drop_preview(None);
+    settle(&mut model, &data);
+    assert_eq!(model.contents_rect(1), expected_contents_rect(1));
+    assert_eq!(model.drop_preview_rect(&data), None);
+}
+
+#[test]
+fn auto_hide_slides_the_panes_to_their_headers_and_back_on_hover() {
+    let (mut model, mut data) = dock_with_panes(2);
+    data.auto_hide = AutoHide::ToHeaders;
+    assert!(model.hide_if_idle(&data));
+    settle(&mut model, &data);
+    assert_eq!(model.header_rect(0).y1, DOCK_SIZE.height);
+
+    // Away from the headers, it stays hidden
+    assert!(!model.pointer_activity(Point::new(DOCK_SIZE.width - 100.0, DOCK_SIZE.height - 200.0)));
+    assert!(model.pointer_activity(header_title_point(&model, 1)));
+    settle(&mut model, &data);
+    assert_eq!(model.contents_rect(0), expected_contents_rect(0));
+    assert_eq!(model.contents_rect(1), expected_contents_rect(1));
+}
+
+#[test]
+fn auto_hide_can_leave_a_strip() {
+    let (mut model, mut data) = dock_with_panes(1);
+    data.auto_hide = AutoHide::ToStrip;
+    assert!(model.hide_if_idle(&data));
+    settle(&mut model, &data);
+    assert_eq!(model.header_rect(0).y0, DOCK_SIZE.height - AUTO_HIDE_STRIP_HEIGHT);
+    // The strip still takes input, so that hovering it brings the panes back
+    assert!(model.input_area().contains(Point::new(model.header_rect(0).center().x, DOCK_SIZE.height - 1.0)));
+}
+
+#[test]
+fn auto_hide_waits_for_drags_and_can_be_turned_off() {
+    let (mut model, mut data) = dock_with_panes(2);
+    assert!(!model.hide_if_idle(&data));
+
+    data.auto_hide = AutoHide::ToHeaders;
+    let start = header_title_point(&model, 0);
+    let end = drag(&mut model, &mut data, start, -50.0, 5);
+    assert!(!model.hide_if_idle(&data));
+    model.pointer_up(end, &mut data);
+    assert!(model.hide_if_idle(&data));
+}
+
+#[test]
+fn the_pressed_pane_is_focused_and_the_hovered_one_is_not_idle() {
+    let (mut model, mut data) = dock_with_panes(2);
+    // Nothing is dimmed until a pane is pressed
+    assert!(!model.is_pane_unfocused(0) && !model.is_pane_unfocused(1));
+    assert!(!model.pointer_down(model.contents_rect(1).center(), 1, &mut data));
+    assert!(model.is_pane_unfocused(0));
+    assert!(!model.is_pane_unfocused(1));
+
+    assert!(model.is_pane_idle(0) && model.is_pane_idle(1));
+    assert!(model.pointer_hover(Some(header_title_point(&model, 0)), &data));
+    assert!(!model.is_pane_idle(0));
+    assert!(!model.pointer_hover(Some(model.contents_rect(0).center()), &data));
+    assert!(model.pointer_hover(None, &data));
+    assert!(model.is_pane_idle(0));
+}
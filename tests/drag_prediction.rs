@@ -0,0 +1,137 @@
+use druid::Point;
+use druid_pane_demo::DockData;
+use druid_pane_demo::dock_model::DockModel;
+use druid_pane_demo::drag_prediction::{DragPredictor, DragSettings};
+use druid_pane_demo::pane_layout::PaneTarget;
+
+mod common;
+use common::{dock_with_panes, header_title_point, pane_ids};
+
+fn target(x_pos: f64) -> PaneTarget {
+    PaneTarget { x_pos, width: 300.0, height: 480.0, y_offset: 0.0 }
+}
+
+// Moves the pointer horizontally by each of the amounts, without releasing it.
+// Returns where the pointer ended up, and how many times the pane order changed.
+fn run_trace(model: &mut DockModel, data: &mut DockData, from: Point, moves: &[f64]) -> (Point, usize) {
+    let mut pos = from;
+    let mut order = pane_ids(data);
+    let mut order_changes = 0;
+    for x_change in moves {
+        pos.x += x_change;
+        model.pointer_move(pos, data);
+        let new_order = pane_ids(data);
+        if new_order != order {
+            order_changes += 1;
+            order = new_order;
+        }
+    }
+    (pos, order_changes)
+}
+
+#[test]
+fn smoothing_ignores_a_single_movement_the_other_way() {
+    let mut predictor = DragPredictor::new(DragSettings::default());
+    for _ in 0..10 {
+        predictor.add_movement(10.0);
+    }
+    predictor.add_movement(-10.0);
+    assert!(predictor.velocity() > 0.0);
+}
+
+#[test]
+fn the_drop_position_leads_in_the_direction_of_movement() {
+    let settings = DragSettings::default();
+    let mut predictor = DragPredictor::new(settings);
+    // At rest, it's the center of the pane
+    assert_eq!(predictor.drop_x_pos(100.0, 300.0), 250.0);
+
+    for _ in 0..20 {
+        predictor.add_movement(10.0);
+    }
+    assert_eq!(predictor.drop_x_pos(100.0, 300.0), 250.0 + 300.0 * settings.swap_lead);
+
+    for _ in 0..40 {
+        predictor.add_movement(-10.0);
+    }
+    assert_eq!(predictor.drop_x_pos(100.0, 300.0), 250.0 - 300.0 * settings.swap_lead);
+}
+
+#[test]
+fn panes_only_swap_once_past_the_hysteresis() {
+    let targets = [target(8.0), target(316.0)];
+    // The second pane's center is at 466
+    let predictor = DragPredictor::new(DragSettings { hysteresis: 12.0, ..DragSettings::default() });
+    assert_eq!(predictor.new_index(&targets, 470.0, 0), 0);
+    assert_eq!(predictor.new_index(&targets, 480.0, 0), 1);
+
+    let predictor = DragPredictor::new(DragSettings { hysteresis: 0.0, ..DragSettings::default() });
+    assert_eq!(predictor.new_index(&targets, 470.0, 0), 1);
+}
+
+#[test]
+fn jitter_near_a_swap_point_does_not_flicker() {
+    let (mut model, mut data) = dock_with_panes(3);
+    let start = header_title_point(&model, 0);
+    assert!(model.pointer_down(start, 1, &mut data));
+    let (pos, order_changes) = run_trace(&mut model, &mut data, start, &[-10.0; 10]);
+    assert_eq!(order_changes, 1);
+    assert_eq!(pane_ids(&data), vec![1, 0, 2]);
+
+    // Shaking the pointer around where it swapped
+    let jitter: Vec<f64> = (0..20).map(|i| if i % 2 == 0 { 3.0 } else { -3.0 }).collect();
+    let (_, order_changes) = run_trace(&mut model, &mut data, pos, &jitter);
+    assert_eq!(order_changes, 0);
+    assert_eq!(pane_ids(&data), vec![1, 0, 2]);
+}
+
+#[test]
+fn the_drop_target_follows_the_prediction() {
+    let (mut model, mut data) = dock_with_panes(3);
+    let start = header_title_point(&model, 0);
+    assert!(model.pointer_down(start, 1, &mut data));
+    assert_eq!(model.drop_target(&data), None);
+    let (pos, _) = run_trace(&mut model, &mut data, start, &[-10.0; 10]);
+    assert_eq!(model.drop_target(&data), Some(1));
+
+    // Within the hysteresis, it stays where the pane is
+    let jitter: Vec<f64> = (0..20).map(|i| if i % 2 == 0 { 3.0 } else { -3.0 }).collect();
+    let mut pos = pos;
+    for x_change in jitter {
+        pos.x += x_change;
+        model.pointer_move(pos, &mut data);
+        assert_eq!(model.drop_target(&data), model.dragging_pane());
+    }
+}
+
+#[test]
+fn moving_back_swaps_back() {
+    let (mut model, mut data) = dock_with_panes(3);
+    let start = header_title_point(&model, 0);
+    assert!(model.pointer_down(start, 1, &mut data));
+    let (pos, _) = run_trace(&mut model, &mut data, start, &[-10.0; 10]);
+    assert_eq!(pane_ids(&data), vec![1, 0, 2]);
+
+    let (_, order_changes) = run_trace(&mut model, &mut data, pos, &[10.0; 15]);
+    assert_eq!(order_changes, 1);
+    assert_eq!(pane_ids(&data), vec![0, 1, 2]);
+}
+
+#[test]
+fn the_swap_lead_is_configurable() {
+    let trace = [-10.0; 25];
+
+    let (mut model, mut data) = dock_with_panes(3);
+    let start = header_title_point(&model, 0);
+    assert!(model.pointer_down(start, 1, &mut data));
+    run_trace(&mut model, &mut data, start, &trace);
+    assert_eq!(pane_ids(&data), vec![1, 0, 2]);
+
+    // Without a lead, the center of the pane needs to pass the center of the next one
+    let (mut model, mut data) = dock_with_panes(3);
+    model.set_drag_settings(DragSettings { swap_lead: 0.0, ..DragSettings::default() });
+    let start = header_title_point(&model, 0);
+    assert!(model.pointer_down(start, 1, &mut data));
+    run_trace(&mut model, &mut data, start, &trace);
+    assert_eq!(pane_ids(&data), vec![0, 1, 2]);
+}
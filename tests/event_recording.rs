@@ -0,0 +1,90 @@
+use druid::{Event, Point};
+use druid_pane_demo::DockData;
+use druid_pane_demo::dock_model::DockModel;
+use druid_pane_demo::event_recording::{EventRecorder, EventReplay, Recording,
+    RecordedEventKind, RecordedModifiers, RecordedMouse};
+
+mod common;
+use common::{DOCK_SIZE, FRAME_INTERVAL, dock_with_panes, header_title_point, pane_ids};
+
+fn mouse(pos: Point, is_pressed: bool) -> RecordedMouse {
+    RecordedMouse {
+        pos: (pos.x, pos.y),
+        buttons: if is_pressed { vec![1] } else { vec![] },
+        button: 1,
+        count: 1,
+        mods: RecordedModifiers { shift: false, ctrl: false, alt: false, meta: false },
+        wheel_delta: (0.0, 0.0),
+    }
+}
+
+// Applies an event to the model the way the dock does, without a window for it
+fn replay_event(model: &mut DockModel, event: &Event, dock: &mut DockData) {
+    match event {
+        Event::MouseDown(mouse_event) | Event::MouseUp(mouse_event) | Event::MouseMove(mouse_event)
+            | Event::Wheel(mouse_event) => {
+            model.pointer_activity(mouse_event.pos);
+        }
+        _ => (),
+    }
+    match event {
+        Event::MouseDown(mouse_event) if mouse_event.buttons.has_left() => {
+            model.pointer_down(mouse_event.pos, mouse_event.count, dock);
+        }
+        Event::MouseMove(mouse_event) => {
+            model.pointer_move(mouse_event.pos, dock);
+            model.pointer_hover(Some(mouse_event.pos), dock);
+        }
+        Event::MouseUp(mouse_event) => {
+            model.pointer_up(mouse_event.pos, dock);
+        }
+        Event::AnimFrame(interval) => {
+            model.animate(*interval, dock);
+        }
+        _ => (),
+    }
+}
+
+// Drags the first pane past the second one, and lets the panes settle
+fn drag_session(start: Point) -> Vec<Event> {
+    let mut events = vec![RecordedEventKind::MouseDown(mouse(start, true)).to_event()];
+    let mut pos = start;
+    for _ in 0..35 {
+        pos.x -= 10.0;
+        events.push(RecordedEventKind::MouseMove(mouse(pos, true)).to_event());
+        events.push(Event::AnimFrame(FRAME_INTERVAL));
+    }
+    events.push(RecordedEventKind::MouseUp(mouse(pos, false)).to_event());
+    events.extend((0..200).map(|_| Event::AnimFrame(FRAME_INTERVAL)));
+    events
+}
+
+#[test]
+fn a_replay_ends_with_the_recorded_layout() {
+    let (mut model, mut data) = dock_with_panes(3);
+    let mut recorder = EventRecorder::new(DOCK_SIZE, &data);
+    for event in drag_session(header_title_point(&model, 0)) {
+        recorder.record(&event);
+        replay_event(&mut model, &event, &mut data);
+    }
+    assert_eq!(pane_ids(&data), vec![1, 0, 2]);
+    let recording = recorder.finish(&data.panes);
+    let recorded_rects: Vec<_> = (0..data.panes.len()).map(|index| model.contents_rect(index)).collect();
+
+    // Replayed from the saved file, into a dock that started out different
+    let json = serde_json::to_string(&recording).unwrap();
+    let recording: Recording = serde_json::from_str(&json).unwrap();
+    let (mut replay_model, mut replay_data) = dock_with_panes(5);
+    recording.apply_initial_state(&mut replay_data);
+    replay_model.sync(&replay_data);
+    let mut replay = EventReplay::new(recording);
+    while !replay.is_finished() {
+        for event in replay.next_frame() {
+            replay_event(&mut replay_model, &event, &mut replay_data);
+        }
+    }
+
+    assert_eq!(pane_ids(&replay_data), pane_ids(&data));
+    let replayed_rects: Vec<_> = (0..replay_data.panes.len()).map(|index| replay_model.contents_rect(index)).collect();
+    assert_eq!(replayed_rects, recorded_rects);
+}
@@ -0,0 +1,136 @@
+use druid_pane_demo::ipc::{apply_request, IpcRequest, IpcResponse};
+
+mod common;
+use common::state_with_panes;
+
+#[test]
+fn requests_are_read_from_json() {
+    let request: IpcRequest = serde_json::from_str(r#"{"command": "set_badge", "pane_id": 2, "badge": 5}"#).unwrap();
+    assert_eq!(request, IpcRequest::SetBadge { pane_id: 2, badge: Some(5) });
+    let request: IpcRequest = serde_json::from_str(r#"{"command": "open"}"#).unwrap();
+    assert_eq!(request, IpcRequest::Open { title: None });
+}
+
+#[test]
+fn opened_panes_can_be_changed_and_closed() {
+    let mut state = state_with_panes(1);
+    let response = apply_request(&mut state, &IpcRequest::Open { title: Some("Conversation X".to_string()) });
+    assert_eq!(response.pane_id, Some(1));
+    assert_eq!(state.docks[0].panes[1].header_text(), "Conversation X");
+
+    assert!(apply_request(&mut state, &IpcRequest::SetBadge { pane_id: 1, badge: Some(3) }).ok);
+    assert_eq!(state.docks[0].panes[1].header_text(), "Conversation X (3)");
+
+    let panes = apply_request(&mut state, &IpcRequest::List).panes.unwrap();
+    assert_eq!(panes.iter().map(|pane| pane.id).collect::<Vec<_>>(), vec![0, 1]);
+    assert_eq!(panes[1].badge, Some(3));
+
+    assert!(apply_request(&mut state, &IpcRequest::Close { pane_id: 0 }).ok);
+    assert_eq!(state.docks[0].panes.len(), 1);
+    assert!(!apply_request(&mut state, &IpcRequest::Close { pane_id: 0 }).ok);
+}
+
+#[test]
+fn focusing_a_minimized_pane_restores_it() {
+    let mut state = state_with_panes(2);
+    state.docks[0].toggle_minimized(1);
+    assert!(apply_request(&mut state, &IpcRequest::Focus { pane_id: 1 }).ok);
+    assert!(!state.docks[0].panes[1].is_minimized);
+}
+
+#[test]
+fn another_launch_opens_its_panes_in_the_running_dock() {
+    let mut state = state_with_panes(1);
+    let args = ["druid-pane-demo", "--open", "Notes", "--open", "Log", "--always-on-top"].map(String::from).to_vec();
+    let response = apply_request(&mut state, &IpcRequest::Launch { args, cwd: None });
+    assert_eq!(response.pane_id, Some(2));
+    assert_eq!(state.docks[0].panes.iter().map(|pane| pane.header_text()).collect::<Vec<_>>(),
+        vec!["Pane 0 header", "Notes", "Log"]);
+    assert!(state.always_on_top);
+
+    let args = ["druid-pane-demo", "--no-such-option"].map(String::from).to_vec();
+    assert!(!apply_request(&mut state, &IpcRequest::Launch { args, cwd: None }).ok);
+}
+
+#[test]
+fn another_launch_finds_its_files_from_where_it_was_started() {
+    let dir = std::env::temp_dir().join(format!("druid-pane-demo-ipc-launch-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("theme.json"), r##"{"pane_background": "#102030"}"##).unwrap();
+
+    let mut state = state_with_panes(1);
+    let args = ["druid-pane-demo", "--theme", "theme.json"].map(String::from).to_vec();
+    let response = apply_request(&mut state, &IpcRequest::Launch { args, cwd: Some(dir.clone()) });
+    assert!(response.ok, "{:?}", response.error);
+    assert_eq!(state.theme.pane_background, druid::Color::rgb8(0x10, 0x20, 0x30));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn the_server_answers_each_line() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::sync::{Arc, Mutex};
+    use druid_pane_demo::ipc_server::{send_request, IpcServer};
+
+    let path = std::env::temp_dir().join(format!("druid-pane-demo-ipc-test-{}.sock", std::process::id()));
+    let state = Arc::new(Mutex::new(state_with_panes(1)));
+    let server_state = state.clone();
+    let server = IpcServer::start(&path, move |request| {
+        apply_request(&mut server_state.lock().unwrap(), &request)
+    }).unwrap();
+
+    let mut stream = UnixStream::connect(server.path()).unwrap();
+    let mut responses = BufReader::new(stream.try_clone().unwrap()).lines();
+    let mut ask = |line: &str| -> IpcResponse {
+        writeln!(stream, "{}", line).unwrap();
+        serde_json::from_str(&responses.next().unwrap().unwrap()).unwrap()
+    };
+    assert_eq!(ask(r#"{"command": "open", "title": "Notes"}"#).pane_id, Some(1));
+    assert_eq!(ask(r#"{"command": "list"}"#).panes.unwrap().len(), 2);
+    assert!(!ask("not json").ok);
+    assert_eq!(state.lock().unwrap().docks[0].panes[1].title.as_deref(), Some("Notes"));
+    // Like a second launch does
+    assert_eq!(send_request(server.path(), &IpcRequest::List).unwrap().panes.unwrap().len(), 2);
+
+    drop(server);
+    assert!(!path.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn the_server_only_replaces_sockets_left_behind() {
+    use std::os::unix::net::UnixListener;
+    use druid_pane_demo::ipc::IpcResponse;
+    use druid_pane_demo::ipc_server::{send_request, IpcServer};
+
+    let answer_ok = |_: IpcRequest| IpcResponse { ok: true, ..IpcResponse::default() };
+    let dir = std::env::temp_dir().join(format!("druid-pane-demo-ipc-sockets-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // Something that isn't a socket is left alone
+    let file_path = dir.join("not-a-socket");
+    std::fs::write(&file_path, "keep me").unwrap();
+    assert!(IpcServer::start(&file_path, answer_ok).is_err());
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "keep me");
+
+    // A socket that nothing listens on anymore is replaced
+    let path = dir.join("dock.sock");
+    drop(UnixListener::bind(&path).unwrap());
+    let server = IpcServer::start(&path, answer_ok).unwrap();
+
+    // A second dock can't take the socket from the one running
+    assert!(IpcServer::start(&path, answer_ok).is_err());
+    assert!(send_request(&path, &IpcRequest::List).unwrap().ok);
+
+    // Once another dock has taken its place, dropping the server leaves that socket alone
+    std::fs::remove_file(&path).unwrap();
+    let other_socket = UnixListener::bind(&path).unwrap();
+    drop(server);
+    assert!(path.exists());
+
+    drop(other_socket);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
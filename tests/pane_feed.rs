@@ -0,0 +1,36 @@
+use druid::Data;
+use druid_pane_demo::pane_feed::{apply_update, PaneFeedUpdate, PaneUpdate};
+
+mod common;
+use common::state_with_panes;
+
+fn update(pane_id: i32, update: PaneUpdate) -> PaneFeedUpdate {
+    PaneFeedUpdate { pane_id, update }
+}
+
+#[test]
+fn updates_only_change_their_pane() {
+    let mut state = state_with_panes(3);
+    let before = state.clone();
+    assert!(apply_update(&mut state, &update(1, PaneUpdate::AppendLine("First".to_string()))));
+    assert!(apply_update(&mut state, &update(1, PaneUpdate::AppendLine("Second".to_string()))));
+    assert!(apply_update(&mut state, &update(1, PaneUpdate::SetBadge(Some(2)))));
+    assert!(apply_update(&mut state, &update(1, PaneUpdate::SetTitle(Some("Log".to_string())))));
+
+    let pane = &state.docks[0].panes[1];
+    assert_eq!(pane.content_text(), "First\nSecond");
+    assert_eq!(pane.header_text(), "Log (2)");
+    // The other panes are the same, so their widgets aren't updated
+    assert!(state.docks[0].panes[0].same(&before.docks[0].panes[0]));
+    assert!(state.docks[0].panes[2].same(&before.docks[0].panes[2]));
+
+    assert!(apply_update(&mut state, &update(1, PaneUpdate::SetText(String::new()))));
+    assert_eq!(state.docks[0].panes[1].content_text(), "Pane 1 content");
+}
+
+#[test]
+fn updates_for_closed_panes_are_ignored() {
+    let mut state = state_with_panes(2);
+    state.docks[0].close_pane(1);
+    assert!(!apply_update(&mut state, &update(1, PaneUpdate::SetText("Late".to_string()))));
+}
@@ -0,0 +1,78 @@
+use druid_pane_demo::pane_widget::{PaneWidget, ScrollPosition};
+
+#[test]
+fn new_panes_stick_to_the_bottom_as_content_is_added() {
+    let position = ScrollPosition::default();
+    assert_eq!(position.offset_for(100.0, 200.0), 0.0);
+    assert_eq!(position.offset_for(500.0, 200.0), 300.0);
+    assert_eq!(position.offset_for(600.0, 200.0), 400.0);
+}
+
+#[test]
+fn scrolling_up_keeps_the_offset_until_scrolling_back_to_the_bottom() {
+    let position = ScrollPosition::scrolled_to(120.0, 500.0, 200.0);
+    assert!(!position.sticks_to_bottom);
+    assert_eq!(position.offset_for(600.0, 200.0), 120.0);
+
+    let position = ScrollPosition::scrolled_to(298.0, 500.0, 200.0);
+    assert!(position.sticks_to_bottom);
+    assert_eq!(position.offset_for(600.0, 200.0), 400.0);
+}
+
+// Stands in for druid's `Scroll`, which clamps its offset to the contents each time it's laid out
+struct ScrollContainer {
+    offset: f64,
+    content_height: f64,
+    viewport_height: f64,
+}
+
+impl ScrollContainer {
+    fn max_offset(&self) -> f64 {
+        (self.content_height - self.viewport_height).max(0.0)
+    }
+
+    // Like `PaneWidget::layout`
+    fn layout(&mut self, pane: &PaneWidget, content_height: f64) -> f64 {
+        self.content_height = content_height;
+        let offset = pane.scroll_position().offset_for(self.content_height, self.viewport_height);
+        self.offset = offset.clamp(0.0, self.max_offset());
+        self.offset
+    }
+
+    // Like `PaneWidget::event`, for a wheel event, or one that doesn't scroll with a delta of 0
+    fn event(&mut self, pane: &mut PaneWidget, delta: f64) {
+        let offset_before = self.offset;
+        self.offset = (self.offset + delta).clamp(0.0, self.max_offset());
+        pane.contents_scrolled(offset_before, self.offset, self.content_height, self.viewport_height);
+    }
+}
+
+#[test]
+fn the_offset_comes_back_after_minimizing_restoring_and_reordering() {
+    let mut pane = PaneWidget::new();
+    let mut scroll = ScrollContainer { offset: 0.0, content_height: 0.0, viewport_height: 200.0 };
+    assert_eq!(scroll.layout(&pane, 500.0), 300.0);
+    scroll.event(&mut pane, -180.0);
+    assert_eq!(pane.scroll_position(), ScrollPosition { offset: 120.0, sticks_to_bottom: false });
+
+    // Minimized, the pane slides down with its size kept, and clicking its header doesn't scroll it
+    scroll.event(&mut pane, 0.0);
+    assert_eq!(scroll.layout(&pane, 500.0), 120.0);
+    // Lines added meanwhile don't pull it to the bottom
+    assert_eq!(scroll.layout(&pane, 600.0), 120.0);
+
+    // Restored
+    assert_eq!(scroll.layout(&pane, 600.0), 120.0);
+
+    // Reordered into a wider spot, where the text wraps onto fewer lines, the offset has to be clamped,
+    // but it's back where it was once the text is as long again
+    assert_eq!(scroll.layout(&pane, 250.0), 50.0);
+    scroll.event(&mut pane, 0.0);
+    assert_eq!(scroll.layout(&pane, 600.0), 120.0);
+    assert_eq!(pane.scroll_position().offset, 120.0);
+
+    // Scrolling back to the bottom sticks to it again
+    scroll.event(&mut pane, 400.0);
+    assert!(pane.scroll_position().sticks_to_bottom);
+    assert_eq!(scroll.layout(&pane, 700.0), 500.0);
+}
@@ -0,0 +1,176 @@
+//! Renders the dock's own painting offscreen, through the same painting as the dock window and its panes,
+//! and compares it against the PNGs in tests/snapshots.
+//! Only what the dock paints itself is covered: the window background, the resize border, the pane backgrounds,
+//! borders and dimming, and the drop preview. The pane widgets need a window to be laid out in, so the header
+//! titles and buttons, and the pane contents, are left out.
+//! Set BLESS_SNAPSHOTS=1 to write the current rendering as the new references.
+//! A reference that doesn't exist fails the test, so that new ones have to be written on purpose.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use druid::piet::{Device, ImageFormat, RenderContext};
+use druid_pane_demo::{DockData, PaneData};
+use druid_pane_demo::dock_model::{DockModel, DropPreview};
+use druid_pane_demo::dock_painting::{self, DockCanvas, DockColors};
+
+mod common;
+use common::{DOCK_SIZE, FRAME_INTERVAL, dock_with_panes, drag, header_title_point, settle};
+
+// Differences within this are from antialiasing and rounding between backends and versions
+const CHANNEL_TOLERANCE: u8 = 3;
+// The fraction of pixels that can be past the tolerance
+const MAX_DIFFERENT_PIXELS: f64 = 0.001;
+
+struct Image {
+    width: usize,
+    height: usize,
+    // RGBA, not premultiplied
+    pixels: Vec<u8>,
+}
+
+// Paints the panes without their widgets, so none of their text or buttons
+struct BitmapCanvas<'a, R>(&'a mut R);
+
+impl<R: RenderContext> DockCanvas for BitmapCanvas<'_, R> {
+    type Render = R;
+
+    fn render_ctx(&mut self) -> &mut R {
+        self.0
+    }
+
+    fn paint_pane_widgets(&mut self, _pane: &PaneData, _opacity: f64) {}
+}
+
+// Paints it like a dock window that fills the dock, and can be resized while it's hidden
+fn render(model: &DockModel, dock: &DockData, show_dock: bool, colors: &DockColors) -> Image {
+    let width = DOCK_SIZE.width as usize;
+    let height = DOCK_SIZE.height as usize;
+    let mut device = Device::new().expect("creating a piet device");
+    let mut target = device.bitmap_target(width, height, 1.0).expect("creating a bitmap target");
+    {
+        let mut rc = target.render_context();
+        dock_painting::paint_window_background(&mut rc, DOCK_SIZE.to_rect(), show_dock, colors);
+        if !show_dock {
+            dock_painting::paint_resize_border(&mut rc, DOCK_SIZE);
+        }
+        dock_painting::paint_panes(&mut BitmapCanvas(&mut rc), model, dock, colors);
+        rc.finish().expect("finishing the render");
+    }
+    let image = target.to_image_buf(ImageFormat::RgbaSeparate).expect("reading back the bitmap");
+    Image { width, height, pixels: image.raw_pixels().to_vec() }
+}
+
+fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("snapshots").join(format!("{}.png", name))
+}
+
+fn write_png(path: &Path, image: &Image) {
+    let file = File::create(path).expect("creating the snapshot file");
+    let mut encoder = png::Encoder::new(BufWriter::new(file), image.width as u32, image.height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().expect("writing the PNG header");
+    writer.write_image_data(&image.pixels).expect("writing the PNG data");
+}
+
+fn read_png(path: &Path) -> Image {
+    let decoder = png::Decoder::new(File::open(path).expect("opening the snapshot file"));
+    let mut reader = decoder.read_info().expect("reading the PNG header");
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).expect("reading the PNG data");
+    assert_eq!(info.color_type, png::ColorType::Rgba, "{:?} is not RGBA", path);
+    pixels.truncate(info.buffer_size());
+    Image { width: info.width as usize, height: info.height as usize, pixels }
+}
+
+fn assert_snapshot(name: &str, actual: Image) {
+    let path = snapshot_path(name);
+    if std::env::var_os("BLESS_SNAPSHOTS").is_some() {
+        eprintln!("Writing the reference for {} to {:?}", name, path);
+        std::fs::create_dir_all(path.parent().unwrap()).expect("creating the snapshot directory");
+        write_png(&path, &actual);
+        return;
+    }
+    assert!(path.exists(), "There is no reference for {} at {:?}. Write it with BLESS_SNAPSHOTS=1, and commit it.", name, path);
+
+    let expected = read_png(&path);
+    assert_eq!((actual.width, actual.height), (expected.width, expected.height), "{} changed size", name);
+    let different_pixels = actual.pixels.chunks(4).zip(expected.pixels.chunks(4))
+        .filter(|(actual_pixel, expected_pixel)| {
+            actual_pixel.iter().zip(expected_pixel.iter())
+                .any(|(actual_channel, expected_channel)| actual_channel.abs_diff(*expected_channel) > CHANNEL_TOLERANCE)
+        })
+        .count();
+    let different_fraction = different_pixels as f64 / (actual.width * actual.height) as f64;
+    if different_fraction > MAX_DIFFERENT_PIXELS {
+        let actual_path = path.with_extension("actual.png");
+        write_png(&actual_path, &actual);
+        panic!("{} differs from its reference in {} pixels. The rendering was written to {:?}",
+            name, different_pixels, actual_path);
+    }
+}
+
+#[test]
+fn dock_shown() {
+    let (model, data) = dock_with_panes(1);
+    assert_snapshot("dock_shown", render(&model, &data, true, &DockColors::default()));
+}
+
+#[test]
+fn dock_hidden() {
+    let (model, data) = dock_with_panes(1);
+    assert_snapshot("dock_hidden", render(&model, &data, false, &DockColors::default()));
+}
+
+#[test]
+fn several_panes() {
+    let (model, data) = dock_with_panes(3);
+    assert_snapshot("several_panes", render(&model, &data, false, &DockColors::default()));
+}
+
+#[test]
+fn dragged_pane() {
+    let (mut model, mut data) = dock_with_panes(3);
+    let start = header_title_point(&model, 0);
+    drag(&mut model, &mut data, start, -150.0, 15);
+    // Part way through the other pane sliding over
+    for _ in 0..5 {
+        model.animate(FRAME_INTERVAL, &data);
+    }
+    assert_snapshot("dragged_pane", render(&model, &data, false, &DockColors::default()));
+}
+
+#[test]
+fn minimized_pane() {
+    let (mut model, mut data) = dock_with_panes(3);
+    data.toggle_minimized(1);
+    settle(&mut model, &data);
+    assert_snapshot("minimized_pane", render(&model, &data, false, &DockColors::default()));
+}
+
+#[test]
+fn idle_panes_faded() {
+    let (mut model, data) = dock_with_panes(3);
+    // Only the hovered pane isn't faded
+    model.pointer_hover(Some(header_title_point(&model, 1)), &data);
+    let colors = DockColors { idle_pane_opacity: 0.4, ..DockColors::default() };
+    assert_snapshot("idle_panes_faded", render(&model, &data, false, &colors));
+}
+
+#[test]
+fn unfocused_panes_dimmed() {
+    let (mut model, data) = dock_with_panes(3);
+    model.focus_pane(data.panes[2].id);
+    let colors = DockColors { dim_unfocused_panes: true, ..DockColors::default() };
+    assert_snapshot("unfocused_panes_dimmed", render(&model, &data, false, &colors));
+}
+
+#[test]
+fn drop_preview() {
+    let (mut model, data) = dock_with_panes(2);
+    // A pane from another dock, between these two
+    model.set_drop_preview(Some(DropPreview { index: 1, width: 200.0, height: 300.0 }));
+    settle(&mut model, &data);
+    assert_snapshot("drop_preview", render(&model, &data, false, &DockColors::default()));
+}
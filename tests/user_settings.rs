@@ -0,0 +1,65 @@
+use druid::Rect;
+use druid_pane_demo::Appearance;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use druid_pane_demo::user_settings::{UserSettings, WindowGeometry, clamp_to_work_areas, settings_path_in};
+
+const LEFT_MONITOR: Rect = Rect::new(0.0, 0.0, 1920.0, 1040.0);
+const RIGHT_MONITOR: Rect = Rect::new(1920.0, 0.0, 3200.0, 984.0);
+
+#[test]
+fn settings_are_saved_and_loaded() {
+    let path = std::env::temp_dir().join("druid-pane-demo-settings-test.json");
+    let settings = UserSettings {
+        appearance: Appearance {
+            background_opacity: 0.25,
+            idle_pane_opacity: 0.5,
+            dim_unfocused_panes: true,
+        },
+        main_window: Some(WindowGeometry { x: 100.0, y: 50.0, width: 800.0, height: 600.0 }),
+        always_on_top: true,
+        show_dock: false,
+    };
+    settings.save(&path).unwrap();
+    let loaded = UserSettings::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, settings);
+}
+
+#[test]
+fn missing_settings_keep_their_defaults() {
+    let settings: UserSettings = serde_json::from_str("{}").unwrap();
+    assert_eq!(settings, UserSettings::default());
+}
+
+#[test]
+fn a_window_on_the_screen_stays_where_it_was() {
+    let window_rect = Rect::new(2000.0, 100.0, 2800.0, 700.0);
+    assert_eq!(clamp_to_work_areas(window_rect, &[LEFT_MONITOR, RIGHT_MONITOR]), Some(window_rect));
+}
+
+#[test]
+fn a_window_partly_off_the_screen_is_moved_onto_the_monitor_it_overlaps_most() {
+    let window_rect = Rect::new(2800.0, 600.0, 3600.0, 1200.0);
+    assert_eq!(clamp_to_work_areas(window_rect, &[LEFT_MONITOR, RIGHT_MONITOR]), Some(Rect::new(2400.0, 384.0, 3200.0, 984.0)));
+}
+
+#[test]
+fn a_window_on_a_monitor_that_is_gone_goes_on_the_first_one_and_shrinks_to_fit() {
+    let window_rect = Rect::new(2000.0, 100.0, 4000.0, 700.0);
+    assert_eq!(clamp_to_work_areas(window_rect, &[LEFT_MONITOR]), Some(Rect::new(0.0, 100.0, 1920.0, 700.0)));
+    assert_eq!(clamp_to_work_areas(window_rect, &[]), None);
+}
+
+#[test]
+fn settings_are_kept_in_the_config_directory() {
+    let config_home = settings_path_in(Some("/home/me/.cfg".into()), Some("/home/me".into()));
+    assert_eq!(config_home, Some(PathBuf::from("/home/me/.cfg/druid-pane-demo/dock-settings.json")));
+    // Without a usable config home, it's the default one in the home directory
+    let unusable: [Option<OsString>; 3] = [None, Some("".into()), Some("relative/config".into())];
+    for config_home in unusable {
+        let path = settings_path_in(config_home, Some("/home/me".into()));
+        assert_eq!(path, Some(PathBuf::from("/home/me/.config/druid-pane-demo/dock-settings.json")));
+    }
+    assert_eq!(settings_path_in(None, None), None);
+}
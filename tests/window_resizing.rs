@@ -0,0 +1,37 @@
+use druid::{Point, Rect, Size, Vec2};
+use druid_pane_demo::window_resizing::{grip_at, resize, ResizeGrip, GRIP_SIZE};
+
+const WINDOW_SIZE: Size = Size::new(1024.0, 600.0);
+const MIN_SIZE: Size = Size::new(400.0, 300.0);
+
+fn grip(left: bool, right: bool, top: bool, bottom: bool) -> ResizeGrip {
+    ResizeGrip { left, right, top, bottom }
+}
+
+#[test]
+fn the_grips_are_along_the_edges_with_corners_at_the_ends() {
+    assert_eq!(grip_at(Point::new(500.0, 300.0), WINDOW_SIZE), None);
+    assert_eq!(grip_at(Point::new(1.0, 300.0), WINDOW_SIZE), Some(grip(true, false, false, false)));
+    assert_eq!(grip_at(Point::new(500.0, WINDOW_SIZE.height - 1.0), WINDOW_SIZE), Some(grip(false, false, false, true)));
+    // Along the bottom, but close enough to the right to be the corner
+    let corner = grip_at(Point::new(WINDOW_SIZE.width - 10.0, WINDOW_SIZE.height - 1.0), WINDOW_SIZE).unwrap();
+    assert_eq!(corner, grip(false, true, false, true));
+    assert!(corner.is_corner());
+    assert_eq!(grip_at(Point::new(GRIP_SIZE + 1.0, 300.0), WINDOW_SIZE), None);
+}
+
+#[test]
+fn resizing_moves_only_the_edges_of_the_grip() {
+    let window_rect = Rect::new(100.0, 100.0, 1124.0, 700.0);
+    assert_eq!(resize(window_rect, grip(true, false, true, false), Vec2::new(-50.0, 20.0), MIN_SIZE),
+        Rect::new(50.0, 120.0, 1124.0, 700.0));
+    assert_eq!(resize(window_rect, grip(false, true, false, false), Vec2::new(30.0, 40.0), MIN_SIZE),
+        Rect::new(100.0, 100.0, 1154.0, 700.0));
+}
+
+#[test]
+fn the_window_stops_shrinking_at_the_minimum_size() {
+    let window_rect = Rect::new(100.0, 100.0, 1124.0, 700.0);
+    assert_eq!(resize(window_rect, grip(true, false, false, true), Vec2::new(2000.0, -2000.0), MIN_SIZE),
+        Rect::new(724.0, 100.0, 1124.0, 400.0));
+}
@@ -0,0 +1,37 @@
+use druid::{Point, Rect, Size};
+use druid_pane_demo::window_snapping::{snap_window_position, SnapArea, SNAP_DISTANCE};
+
+const WINDOW_SIZE: Size = Size::new(800.0, 600.0);
+// With a taskbar along the bottom
+const MONITOR: SnapArea = SnapArea {
+    bounds: Rect::new(0.0, 0.0, 1920.0, 1080.0),
+    work_area: Rect::new(0.0, 0.0, 1920.0, 1040.0),
+};
+
+#[test]
+fn the_window_snaps_into_the_bottom_right_corner_of_the_work_area() {
+    let pos = Point::new(1920.0 - 800.0 - 10.0, 1040.0 - 600.0 + 5.0);
+    assert_eq!(snap_window_position(pos, WINDOW_SIZE, &[MONITOR]), Point::new(1120.0, 440.0));
+}
+
+#[test]
+fn the_window_snaps_to_the_left_and_top_edges() {
+    let pos = Point::new(-12.0, 7.0);
+    assert_eq!(snap_window_position(pos, WINDOW_SIZE, &[MONITOR]), Point::ZERO);
+}
+
+#[test]
+fn the_window_moves_freely_away_from_the_edges() {
+    let pos = Point::new(SNAP_DISTANCE + 1.0, 300.0);
+    assert_eq!(snap_window_position(pos, WINDOW_SIZE, &[MONITOR]), pos);
+}
+
+#[test]
+fn the_window_snaps_to_the_edge_between_monitors() {
+    let right_monitor = SnapArea {
+        bounds: Rect::new(1920.0, 0.0, 3840.0, 1080.0),
+        work_area: Rect::new(1920.0, 0.0, 3840.0, 1080.0),
+    };
+    let pos = Point::new(1930.0, 300.0);
+    assert_eq!(snap_window_position(pos, WINDOW_SIZE, &[MONITOR, right_monitor]), Point::new(1920.0, 300.0));
+}